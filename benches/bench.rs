@@ -1,4 +1,5 @@
 use criterion::BenchmarkId;
+use fraktal::boundary::FillAlgorithm;
 use fraktal::presets::PRESETS;
 use fraktal::Engine;
 use fraktal::EngineSettings;
@@ -75,6 +76,43 @@ pub fn presets(c: &mut criterion::Criterion) {
     }
 }
 
+pub fn fill_algorithms(c: &mut criterion::Criterion) {
+    let mut group = c.benchmark_group("Fill algorithms");
+    let size = (1920, 1080);
+    for (name, fill_algorithm) in [
+        ("Trisect", FillAlgorithm::Trisect),
+        ("BoundaryTrace", FillAlgorithm::BoundaryTrace),
+    ] {
+        group.bench_with_input(
+            BenchmarkId::new(name, format!("{}x{}", size.0, size.1)),
+            &size,
+            |bencher, (width, height)| {
+                let mut engines: Vec<Engine> = PRESETS
+                    .iter()
+                    .map(|preset| {
+                        let mut settings = EngineSettings::restore(preset.state)
+                            .unwrap()
+                            .set_size(*width, *height)
+                            .set_fill_algorithm(fill_algorithm);
+                        let mut engine = Engine::new(&settings);
+                        engine.apply_settings(&mut settings);
+                        engine
+                    })
+                    .collect();
+                bencher.iter(|| {
+                    engines
+                        .iter_mut()
+                        .map(|engine| {
+                            engine.reset();
+                            engine.compute(usize::MAX)
+                        })
+                        .sum::<usize>()
+                });
+            },
+        );
+    }
+}
+
 criterion::criterion_group! {
     name = bench_default;
     config = criterion::Criterion::default()
@@ -95,4 +133,15 @@ criterion::criterion_group! {
         .measurement_time(::std::time::Duration::from_millis(2000));
     targets = presets
 }
-criterion::criterion_main!(bench_default, bench_presets);
+
+criterion::criterion_group! {
+    name = bench_fill_algorithms;
+    config = criterion::Criterion::default()
+        .significance_level(0.01)
+        .noise_threshold(0.05)
+        .sample_size(10)
+        .warm_up_time(::std::time::Duration::from_millis(1000))
+        .measurement_time(::std::time::Duration::from_millis(4000));
+    targets = fill_algorithms
+}
+criterion::criterion_main!(bench_default, bench_presets, bench_fill_algorithms);
@@ -0,0 +1,204 @@
+//! Zoom animations between keyframes of [EngineSettings].
+
+use crate::Engine;
+use crate::EngineSettings;
+
+/// Where one frame lies in an [Animation]: between keyframes `from` and
+/// `from + 1`, a fraction `t` of the way.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct FramePosition {
+    from: usize,
+    t: f64,
+}
+
+/// An animation through a list of keyframes, with the same number of frames
+/// between each pair of consecutive keyframes.
+///
+/// Scale is interpolated exponentially, so that zooming appears to go at a
+/// steady pace. The center moves in step with the scale, so that zooming
+/// into the center of the next keyframe appears to go straight towards it.
+/// Iteration limits are interpolated linearly. Gradients and other coloring
+/// settings are cross-faded.
+#[derive(Clone, Debug)]
+pub struct Animation {
+    keyframes: Vec<EngineSettings>,
+    frames_per_keyframe: usize,
+}
+
+impl Animation {
+    pub fn new(keyframes: Vec<EngineSettings>, frames_per_keyframe: usize) -> Self {
+        Self {
+            keyframes,
+            frames_per_keyframe: frames_per_keyframe.max(1),
+        }
+    }
+
+    /// The number of frames, including both the first and the last keyframe.
+    pub fn len(&self) -> usize {
+        self.keyframes.len().saturating_sub(1) * self.frames_per_keyframe
+            + usize::from(!self.keyframes.is_empty())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keyframes.is_empty()
+    }
+
+    fn position(&self, frame: usize) -> FramePosition {
+        let from = frame / self.frames_per_keyframe;
+        if from + 1 >= self.keyframes.len() {
+            FramePosition {
+                from: self.keyframes.len().saturating_sub(2),
+                t: if self.keyframes.len() > 1 { 1.0 } else { 0.0 },
+            }
+        } else {
+            FramePosition {
+                from,
+                t: (frame % self.frames_per_keyframe) as f64 / self.frames_per_keyframe as f64,
+            }
+        }
+    }
+
+    /// The settings for `frame`, with the coloring of the keyframe before it
+    /// and of the keyframe after it, and how far to fade from the former to
+    /// the latter.
+    pub fn frame_settings(&self, frame: usize) -> (EngineSettings, EngineSettings, f64) {
+        let FramePosition { from, t } = self.position(frame);
+        let a = &self.keyframes[from];
+        let b = self.keyframes.get(from + 1).unwrap_or(a);
+
+        let (scale_a, scale_b) = (a.get_scale(), b.get_scale());
+        let scale = scale_a * (scale_b / scale_a).powf(t);
+
+        // The fraction of the way the center has moved: the same as the
+        // fraction of the way the scale has shrunk or grown
+        let ratio = scale_b / scale_a;
+        let moved = if (ratio - 1.0).abs() < 1e-9 {
+            t
+        } else {
+            (1.0 - ratio.powf(t)) / (1.0 - ratio)
+        };
+        let ((re_a, im_a), (re_b, im_b)) = (a.get_center(), b.get_center());
+        let center = (re_a + (re_b - re_a) * moved, im_a + (im_b - im_a) * moved);
+
+        let iteration_limit = (a.get_iteration_limit() as f64 * (1.0 - t)
+            + b.get_iteration_limit() as f64 * t)
+            .round() as usize;
+
+        let frame_settings = |keyframe: &EngineSettings| {
+            keyframe
+                .clone()
+                .set_view(center, scale)
+                .set_iteration_limit(iteration_limit)
+        };
+        (frame_settings(a), frame_settings(b), t)
+    }
+
+    /// Render `frame` at the given size, as RGBA bytes row by row.
+    pub fn render_frame(&self, frame: usize, width: usize, height: usize) -> Vec<u8> {
+        let (a, b, t) = self.frame_settings(frame);
+        let mut settings = if t == 1.0 { b.clone() } else { a.clone() }.set_size(width, height);
+        let mut engine = Engine::new(&settings);
+        let mut render = |settings: &mut EngineSettings| {
+            engine.apply_settings(settings);
+            engine.compute(usize::MAX);
+            engine.render();
+            engine.image_data().to_vec()
+        };
+
+        let mut pixels = render(&mut settings);
+        if t == 0.0 || t == 1.0 || a.serialize() == b.serialize() {
+            return pixels;
+        }
+
+        // Both keyframes share the view, so the escape counts computed for
+        // `a` only need coloring like `b`, unless that needs more data
+        let mut recolored = settings.set_coloring_of(&b);
+        for (a, b) in pixels.iter_mut().zip(render(&mut recolored)) {
+            *a = (f64::from(*a) * (1.0 - t) + f64::from(b) * t).round() as u8;
+        }
+        pixels
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Animation;
+    use crate::presets::PRESETS;
+    use crate::Engine;
+    use crate::EngineSettings;
+
+    fn keyframes() -> Vec<EngineSettings> {
+        let first = EngineSettings::restore(PRESETS[0].state).unwrap();
+        let last = EngineSettings::restore(PRESETS[1].state).unwrap();
+        vec![first, last]
+    }
+
+    #[test]
+    fn frame_count() {
+        assert_eq!(Animation::new(vec![], 10).len(), 0);
+        assert_eq!(Animation::new(keyframes()[..1].to_vec(), 10).len(), 1);
+        assert_eq!(Animation::new(keyframes(), 10).len(), 11);
+    }
+
+    #[test]
+    fn interpolates_between_keyframes() {
+        let keyframes = keyframes();
+        let animation = Animation::new(keyframes.clone(), 10);
+
+        let (first, _, t) = animation.frame_settings(0);
+        assert_eq!(t, 0.0);
+        assert_eq!(first.serialize(), keyframes[0].serialize());
+
+        let (_, last, t) = animation.frame_settings(10);
+        assert_eq!(t, 1.0);
+        assert_eq!(last.get_center(), keyframes[1].get_center());
+        assert!((last.get_scale() / keyframes[1].get_scale() - 1.0).abs() < 1e-9);
+
+        // Scale changes by the same factor every frame
+        let scales: Vec<f64> = (0..=10)
+            .map(|frame| animation.frame_settings(frame).0.get_scale())
+            .collect();
+        let factor = scales[1] / scales[0];
+        for pair in scales.windows(2) {
+            assert!((pair[1] / pair[0] / factor - 1.0).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn first_frame_matches_keyframe() {
+        let keyframes = keyframes();
+        let animation = Animation::new(keyframes.clone(), 4);
+
+        let mut settings = keyframes[0].clone().set_size(40, 30);
+        let mut engine = Engine::new(&settings);
+        engine.apply_settings(&mut settings);
+        engine.compute(usize::MAX);
+        engine.render();
+
+        assert!(animation.render_frame(0, 40, 30) == engine.image_data());
+        assert_eq!(animation.render_frame(2, 40, 30).len(), 40 * 30 * 4);
+    }
+
+    #[test]
+    fn cross_fade_matches_separate_renders() {
+        let keyframes = keyframes();
+        let animation = Animation::new(keyframes, 4);
+        let (a, b, t) = animation.frame_settings(2);
+        assert!(t > 0.0 && t < 1.0);
+
+        let render = |settings: EngineSettings| {
+            let mut settings = settings.set_size(40, 30);
+            let mut engine = Engine::new(&settings);
+            engine.apply_settings(&mut settings);
+            engine.compute(usize::MAX);
+            engine.render();
+            engine.image_data().to_vec()
+        };
+        let expected: Vec<u8> = render(a)
+            .into_iter()
+            .zip(render(b))
+            .map(|(a, b)| (f64::from(a) * (1.0 - t) + f64::from(b) * t).round() as u8)
+            .collect();
+        assert!(animation.render_frame(2, 40, 30) == expected);
+    }
+}
@@ -0,0 +1,531 @@
+//! Render a state string, preset or PNG file with embedded settings to a PNG
+//! file, or an animation between several of them to a sequence of frames,
+//! without a browser.
+
+use std::error::Error;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io::BufReader;
+use std::io::BufWriter;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Duration;
+use std::time::Instant;
+
+use fraktal::animation::Animation;
+use fraktal::expmap::ExpStrip;
+use fraktal::export::read_png_settings;
+use fraktal::export::write_palette_cycle;
+use fraktal::export::write_png;
+use fraktal::export::PngRowWriter;
+use fraktal::export::Y4mWriter;
+use fraktal::presets::PRESETS;
+use fraktal::stats::format_throughput;
+use fraktal::tiles::Checkpoint;
+use fraktal::tiles::TileGrid;
+use fraktal::Engine;
+use fraktal::EngineSettings;
+use fraktal::PivotScale;
+
+const USAGE: &str = "\
+Usage: fraktal-render [OPTIONS] <STATE | PRESET | FILE.png>
+       fraktal-render [OPTIONS] --animate <FRAMES> <KEYFRAME>...
+
+Render a state string, as found in the `state` URL parameter, the name of a
+preset, or the settings embedded in a PNG file written by fraktal to a PNG
+file.
+
+With --animate, zoom from each keyframe to the next, given the same way, in
+FRAMES frames. The frames are written as numbered PNG files to the directory
+<FILE>, or as a YUV4MPEG2 stream if <FILE> ends in .y4m or is - for standard
+output, for example to pipe into `ffmpeg -i - zoom.mp4`.
+
+Options:
+  -w, --width <PIXELS>   Width of the image [default: 1920]
+  -H, --height <PIXELS>  Height of the image [default: 1080]
+  -o, --output <FILE>    File to write [default: fraktal.png, or frames/
+                         with --animate]
+  -a, --animate <FRAMES> Animate between keyframes, with this many frames
+                         from each keyframe to the next
+      --exp-map          Zoom straight into the center of the deeper of two
+                         keyframes, resampling each frame from one strip of
+                         the exponential map rather than rendering it. Much
+                         faster for deep zooms, but the strip is held in
+                         memory, and colors are fixed to the deeper keyframe.
+                         A gradient stretched over the escape range is
+                         stretched over that of a smaller copy of the strip.
+  -c, --palette-cycle <FRAMES>
+                         Write an animated PNG of one full cycle of the
+                         colors, in this many frames
+      --fps <FPS>        Frame rate of a YUV4MPEG2 stream or of a cycle of
+                         the colors [default: 30]
+  -t, --tile-size <PIXELS>
+                         Render in tiles of this size, so that memory use
+                         doesn't grow with the size of the image. Rows of
+                         tiles are written to <FILE>.part as they finish,
+                         and an interrupted render run again with the same
+                         arguments continues where it left off. A gradient
+                         stretched over the escape range takes a first pass
+                         over the tiles to measure it.
+  -h, --help             Print this message";
+
+/// How often to report progress.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// How many rows of an exponential map strip to render at a time.
+const STRIP_BAND_ROWS: usize = 256;
+
+/// The size, in pixels across, of the preview that the escape range shared
+/// between bands of a strip is measured on.
+const PREVIEW_SIZE: usize = 256;
+
+struct Args {
+    width: usize,
+    height: usize,
+    output: String,
+    tile_size: Option<usize>,
+    animate: Option<usize>,
+    exp_map: bool,
+    palette_cycle: Option<usize>,
+    fps: usize,
+    states: Vec<String>,
+}
+
+/// Parse the command line, or return `None` if help was asked for.
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<Args>, String> {
+    let mut width = 1920;
+    let mut height = 1080;
+    let mut output = None;
+    let mut tile_size = None;
+    let mut animate = None;
+    let mut exp_map = false;
+    let mut palette_cycle = None;
+    let mut fps = 30;
+    let mut states = Vec::new();
+
+    fn parse_size(flag: &str, value: Option<String>) -> Result<usize, String> {
+        value
+            .ok_or_else(|| format!("Missing value for {flag}"))?
+            .parse()
+            .ok()
+            .filter(|size| *size > 0)
+            .ok_or_else(|| format!("Invalid value for {flag}"))
+    }
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-w" | "--width" => width = parse_size(&arg, args.next())?,
+            "-H" | "--height" => height = parse_size(&arg, args.next())?,
+            "-t" | "--tile-size" => tile_size = Some(parse_size(&arg, args.next())?),
+            "-a" | "--animate" => animate = Some(parse_size(&arg, args.next())?),
+            "--exp-map" => exp_map = true,
+            "-c" | "--palette-cycle" => palette_cycle = Some(parse_size(&arg, args.next())?),
+            "--fps" => fps = parse_size(&arg, args.next())?,
+            "-o" | "--output" => {
+                output = Some(
+                    args.next()
+                        .ok_or_else(|| format!("Missing value for {arg}"))?,
+                )
+            }
+            "-h" | "--help" => return Ok(None),
+            _ if arg.starts_with('-') => return Err(format!("Unknown option: {arg}")),
+            _ => states.push(arg),
+        }
+    }
+
+    match (animate, states.len()) {
+        (_, 0) => return Err(USAGE.to_string()),
+        (None, 1) => {}
+        (None, _) => return Err(format!("Unexpected argument: {}", states[1])),
+        (Some(_), 1) => return Err("Animating needs at least two keyframes".to_string()),
+        (Some(_), _) if tile_size.is_some() => {
+            return Err("Animations can't be rendered in tiles".to_string())
+        }
+        (Some(_), _) => {}
+    }
+    if exp_map && animate.is_none() {
+        return Err("--exp-map only applies with --animate".to_string());
+    }
+    if palette_cycle.is_some() && (animate.is_some() || tile_size.is_some()) {
+        return Err("Cycles of the colors can't be animated or rendered in tiles".to_string());
+    }
+    let default_output = if animate.is_some() {
+        "frames"
+    } else {
+        "fraktal.png"
+    };
+
+    Ok(Some(Args {
+        width,
+        height,
+        output: output.unwrap_or_else(|| default_output.to_string()),
+        tile_size,
+        animate,
+        exp_map,
+        palette_cycle,
+        fps,
+        states,
+    }))
+}
+
+fn restore(state: &str) -> Result<EngineSettings, String> {
+    if state.to_ascii_lowercase().ends_with(".png") {
+        let file = File::open(state).map_err(|err| format!("Failed to open {state}: {err}"))?;
+        return read_png_settings(BufReader::new(file))
+            .map_err(|err| format!("Failed to restore settings from {state}: {err}"));
+    }
+
+    let preset = PRESETS
+        .iter()
+        .find(|preset| preset.name.eq_ignore_ascii_case(state));
+    EngineSettings::try_restore(preset.map(|preset| preset.state).unwrap_or(state)).map_err(|err| {
+        let presets: Vec<&str> = PRESETS.iter().map(|preset| preset.name).collect();
+        format!(
+            "Not a valid state string or preset name: {err}\nPresets: {}",
+            presets.join(", ")
+        )
+    })
+}
+
+/// The escape range of a low resolution `preview`, to share between the
+/// bands of one strip if its gradient is stretched over the escape range,
+/// so that they are colored alike.
+fn shared_escape_range(settings: &EngineSettings, mut preview: Engine) -> Option<(usize, usize)> {
+    if settings.get_gradient().get_pivot_scale() != PivotScale::EscapeRange {
+        return None;
+    }
+    preview.compute(usize::MAX);
+    preview.escape_range()
+}
+
+/// Compute until `engine` is done, reporting progress on stderr.
+fn compute_with_progress(engine: &mut Engine, clock: &Instant, label: &str) {
+    let mut stderr = std::io::stderr();
+    loop {
+        engine.compute_for(PROGRESS_INTERVAL, clock);
+        let stats = engine.stats();
+        let _ = write!(
+            stderr,
+            "\r{label}: {:5.1}% {:<24}",
+            stats.progress() * 100.0,
+            format!("({})", format_throughput(stats.iterations_per_second()))
+        );
+        if stats.is_done() {
+            break;
+        }
+    }
+    let _ = writeln!(stderr);
+}
+
+fn render(args: &Args) -> Result<(), String> {
+    if let Some(frames) = args.animate {
+        return render_animation(args, frames);
+    }
+    let settings = restore(&args.states[0])?.set_size(args.width, args.height);
+    match args.tile_size {
+        None => render_whole(args, settings),
+        Some(tile_size) => render_tiled(
+            args,
+            settings,
+            TileGrid::new(args.width, args.height, tile_size),
+        ),
+    }?;
+    eprintln!("Wrote {}", args.output);
+    Ok(())
+}
+
+fn render_whole(args: &Args, mut settings: EngineSettings) -> Result<(), String> {
+    let mut engine = Engine::new(&settings);
+    engine.apply_settings(&mut settings);
+    compute_with_progress(&mut engine, &Instant::now(), "Rendering");
+    engine.render();
+
+    let mut write = || -> Result<(), Box<dyn Error>> {
+        let mut writer = BufWriter::new(File::create(&args.output)?);
+        if let Some(frames) = args.palette_cycle {
+            write_palette_cycle(
+                &mut writer,
+                &mut engine,
+                frames,
+                Duration::from_secs(1) / args.fps as u32,
+                Some(&settings),
+            )?;
+        } else {
+            write_png(
+                &mut writer,
+                args.width,
+                args.height,
+                engine.image_data(),
+                Some(&settings),
+            )?;
+        }
+        writer.flush()?;
+        Ok(())
+    };
+    write().map_err(|err| format!("Failed to write {}: {err}", args.output))
+}
+
+/// The escape range of the whole image, if its gradient is stretched over
+/// the escape range, measured in a first pass over all tiles so that they
+/// are colored just like one render of the whole image.
+fn tiles_escape_range(
+    settings: &EngineSettings,
+    grid: &TileGrid,
+    clock: &Instant,
+) -> Option<(usize, usize)> {
+    if settings.get_gradient().get_pivot_scale() != PivotScale::EscapeRange {
+        return None;
+    }
+    grid.tiles()
+        .enumerate()
+        .filter_map(|(i, tile)| {
+            let mut engine = Engine::new_tile(settings, grid, &tile);
+            compute_with_progress(
+                &mut engine,
+                clock,
+                &format!("Measuring tile {}/{}", i + 1, grid.len()),
+            );
+            engine.escape_range()
+        })
+        .reduce(|(low, high), (tile_low, tile_high)| (low.min(tile_low), high.max(tile_high)))
+}
+
+fn render_tiled(args: &Args, settings: EngineSettings, grid: TileGrid) -> Result<(), String> {
+    if settings.get_render_mode().is_accumulative() {
+        return Err("Only the escape time render mode can be rendered in tiles".to_string());
+    }
+    let state = settings
+        .serialize()
+        .ok_or_else(|| "Failed to serialize settings".to_string())?;
+
+    // The image is written to a file of its own until it is done
+    let part_path = PathBuf::from(format!("{}.part", args.output));
+    let checkpoint_path = PathBuf::from(format!("{}.checkpoint", args.output));
+    let mut checkpoint = Checkpoint::open(&checkpoint_path, &state, &grid)
+        .map_err(|err| format!("Failed to open checkpoint: {err}"))?;
+    let (first_row, mut writer) = match checkpoint.finished() {
+        Some((rows, position)) => {
+            eprintln!(
+                "Resuming with {rows} of {} rows of tiles finished",
+                grid.rows()
+            );
+            let writer = OpenOptions::new()
+                .write(true)
+                .open(&part_path)
+                .and_then(|file| PngRowWriter::resume(file, grid.width, position))
+                .map_err(|err| format!("Failed to resume {}: {err}", part_path.display()))?;
+            (rows, writer)
+        }
+        None => {
+            let writer = File::create(&part_path)
+                .map_err(Into::into)
+                .and_then(|file| {
+                    PngRowWriter::create(file, grid.width, grid.height, Some(&settings))
+                })
+                .map_err(|err| format!("Failed to write {}: {err}", part_path.display()))?;
+            (0, writer)
+        }
+    };
+
+    let clock = Instant::now();
+    let escape_range = match checkpoint.escape_range() {
+        Some(escape_range) => Some(escape_range),
+        None => {
+            let escape_range = tiles_escape_range(&settings, &grid, &clock);
+            if let Some(escape_range) = escape_range {
+                checkpoint
+                    .record_escape_range(escape_range)
+                    .map_err(|err| format!("Failed to write checkpoint: {err}"))?;
+            }
+            escape_range
+        }
+    };
+    // Only one row of tiles is held in memory at a time
+    let mut strip = vec![0; grid.width * grid.tile_size * 4];
+    for row in first_row..grid.rows() {
+        let mut strip_height = 0;
+        for tile in grid.row(row) {
+            let mut engine = Engine::new_tile(&settings, &grid, &tile);
+            engine.share_escape_range(escape_range);
+            compute_with_progress(
+                &mut engine,
+                &clock,
+                &format!(
+                    "Tile {}/{}",
+                    row * grid.columns() + tile.col + 1,
+                    grid.len()
+                ),
+            );
+            engine.render();
+
+            let tile_row_len = tile.width * 4;
+            for (y, tile_row) in engine.image_data().chunks_exact(tile_row_len).enumerate() {
+                let start = (y * grid.width + tile.x0) * 4;
+                strip[start..start + tile_row_len].copy_from_slice(tile_row);
+            }
+            strip_height = tile.height;
+        }
+
+        let mut write = || -> Result<(), Box<dyn Error>> {
+            let position = writer.write_rows(&strip[..grid.width * strip_height * 4])?;
+            checkpoint.mark_finished(row, position)?;
+            Ok(())
+        };
+        write().map_err(|err| format!("Failed to write {}: {err}", part_path.display()))?;
+    }
+
+    writer
+        .finish()
+        .map_err(|err| format!("Failed to write {}: {err}", part_path.display()))?;
+    std::fs::rename(&part_path, &args.output)
+        .map_err(|err| format!("Failed to write {}: {err}", args.output))?;
+    std::fs::remove_file(&checkpoint_path)
+        .map_err(|err| format!("Failed to remove {}: {err}", checkpoint_path.display()))
+}
+
+fn render_animation(args: &Args, frames_per_keyframe: usize) -> Result<(), String> {
+    let keyframes = args
+        .states
+        .iter()
+        .map(|state| restore(state))
+        .collect::<Result<Vec<_>, _>>()?;
+    if args.exp_map {
+        return render_exp_map(args, &keyframes, frames_per_keyframe);
+    }
+
+    let animation = Animation::new(keyframes, frames_per_keyframe);
+    write_frames(args, animation.len(), |frame| {
+        let (settings, _, _) = animation.frame_settings(frame);
+        (
+            animation.render_frame(frame, args.width, args.height),
+            settings,
+        )
+    })
+}
+
+/// Zoom straight into the center of the deeper of two keyframes, with its
+/// coloring, by resampling one strip of the exponential map.
+fn render_exp_map(args: &Args, keyframes: &[EngineSettings], frames: usize) -> Result<(), String> {
+    let [from, to] = keyframes else {
+        return Err("The exponential map zooms between exactly two keyframes".to_string());
+    };
+    let (from_scale, to_scale) = (from.get_scale(), to.get_scale());
+    let deeper = if to_scale <= from_scale { to } else { from };
+    if deeper.get_render_mode().is_accumulative() {
+        return Err(
+            "Only the escape time render mode can be rendered with the exponential map".to_string(),
+        );
+    }
+
+    let center = deeper.get_center();
+    let strip = ExpStrip::for_zoom(
+        center,
+        ExpStrip::default_width(args.width, args.height),
+        (args.width, args.height),
+        from_scale.max(to_scale),
+        from_scale.min(to_scale),
+    );
+    eprintln!("Strip of {}x{} pixels", strip.width(), strip.height());
+
+    // Rendering in bands bounds the memory used by each engine, which is
+    // many times that of the finished pixels
+    let escape_range = shared_escape_range(
+        deeper,
+        Engine::new_strip(
+            deeper,
+            &strip.downsampled(strip.width().div_ceil(PREVIEW_SIZE)),
+        ),
+    );
+    let clock = Instant::now();
+    let bands = strip.height().div_ceil(STRIP_BAND_ROWS);
+    let mut rgba = Vec::with_capacity(strip.width() * strip.height() * 4);
+    for band in 0..bands {
+        let mut engine =
+            Engine::new_strip(deeper, &strip.band(band * STRIP_BAND_ROWS, STRIP_BAND_ROWS));
+        engine.share_escape_range(escape_range);
+        compute_with_progress(&mut engine, &clock, &format!("Band {}/{bands}", band + 1));
+        engine.render();
+        rgba.extend_from_slice(engine.image_data());
+    }
+
+    write_frames(args, frames + 1, |frame| {
+        let scale = from_scale * (to_scale / from_scale).powf(frame as f64 / frames as f64);
+        (
+            strip.reconstruct(&rgba, args.width, args.height, scale),
+            deeper.clone().set_view(center, scale),
+        )
+    })
+}
+
+/// Write `count` frames, as given by `frame` along with their settings, as a
+/// YUV4MPEG2 stream or as numbered PNG files, depending on the output.
+fn write_frames(
+    args: &Args,
+    count: usize,
+    mut frame: impl FnMut(usize) -> (Vec<u8>, EngineSettings),
+) -> Result<(), String> {
+    let mut stderr = std::io::stderr();
+
+    if args.output == "-" || args.output.to_ascii_lowercase().ends_with(".y4m") {
+        let mut write = || -> Result<(), Box<dyn Error>> {
+            let writer: Box<dyn Write> = if args.output == "-" {
+                Box::new(std::io::stdout().lock())
+            } else {
+                Box::new(File::create(&args.output)?)
+            };
+            let mut writer =
+                Y4mWriter::new(BufWriter::new(writer), args.width, args.height, args.fps)?;
+            for i in 0..count {
+                let _ = write!(stderr, "\rFrame {}/{count}", i + 1);
+                writer.write_frame(&frame(i).0)?;
+            }
+            writer.flush()?;
+            Ok(())
+        };
+        write().map_err(|err| format!("Failed to write {}: {err}", args.output))?;
+        let _ = writeln!(stderr);
+        if args.output != "-" {
+            eprintln!("Wrote {}", args.output);
+        }
+        return Ok(());
+    }
+
+    let dir = Path::new(&args.output);
+    std::fs::create_dir_all(dir)
+        .map_err(|err| format!("Failed to create {}: {err}", dir.display()))?;
+    for i in 0..count {
+        let _ = write!(stderr, "\rFrame {}/{count}", i + 1);
+        let (rgba, settings) = frame(i);
+        let path = dir.join(format!("frame-{i:05}.png"));
+
+        let write = || -> Result<(), Box<dyn Error>> {
+            let mut writer = BufWriter::new(File::create(&path)?);
+            write_png(&mut writer, args.width, args.height, &rgba, Some(&settings))?;
+            writer.flush()?;
+            Ok(())
+        };
+        write().map_err(|err| format!("Failed to write {}: {err}", path.display()))?;
+    }
+    let _ = writeln!(stderr);
+    eprintln!("Wrote {count} frames to {}", dir.display());
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let result = parse_args(std::env::args().skip(1)).and_then(|args| match args {
+        Some(args) => render(&args),
+        None => {
+            println!("{USAGE}");
+            Ok(())
+        }
+    });
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("{message}");
+            ExitCode::FAILURE
+        }
+    }
+}
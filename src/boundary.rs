@@ -0,0 +1,434 @@
+//! Boundary tracing: compute only the contours between areas of equal escape
+//! count, then fill in the areas they enclose.
+//!
+//! Tracing starts from the edges of the region, and follows a contour into
+//! the next one wherever they come within a pixel of each other. An area that
+//! is left untraced is filled in only if all of its border has the same
+//! escape count, and only if that escape count can be filled in: inside the
+//! set, since the set has no holes, just as [RectRegion::trisect] assumes.
+//! Other areas hide a contour that was never reached, such as one around an
+//! island in a band of the exterior, so their edges are traced as well, or
+//! they are computed pixel by pixel. The image is therefore the same as with
+//! trisecting, but areas of the set interior are skipped whatever their
+//! shape, rather than only when split into rectangles.
+//!
+//! Like trisecting, this takes an area enclosed by pixels inside the set to
+//! be inside the set too. Filaments of the exterior thinner than a pixel can
+//! cross such an area between pixels, and both then fill them in, though not
+//! always at the same pixels.
+
+use std::collections::HashSet;
+use std::collections::VecDeque;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::rect::RectRegion;
+
+/// How [crate::Engine::compute] finds the pixels it can fill in without
+/// computing them.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub enum FillAlgorithm {
+    /// Recursively split regions in three, and fill regions whose border is
+    /// entirely inside the set.
+    #[default]
+    Trisect,
+
+    /// Trace the contours of equal escape count, and fill the areas they
+    /// enclose.
+    BoundaryTrace,
+}
+
+/// The pixels that a [BoundaryTrace] reads and writes.
+pub trait Pixels {
+    /// Compute the escape count of the pixel at `(x, y)`.
+    fn compute(&mut self, x: i32, y: i32) -> usize;
+
+    /// Whether an area whose whole border has `escape_count`, and which
+    /// spans `width` by `height` pixels, can be filled in without computing
+    /// it. Not, for example, if per-pixel orbit data is needed.
+    fn fillable(&self, escape_count: usize, width: usize, height: usize) -> bool;
+
+    /// Set the escape count of the pixel at `(x, y)` without computing it.
+    fn fill(&mut self, x: i32, y: i32, escape_count: usize);
+}
+
+const LOADED: u8 = 1;
+const QUEUED: u8 = 2;
+
+/// A boundary trace of one region in progress.
+#[derive(Debug)]
+pub struct BoundaryTrace {
+    region: RectRegion,
+    flags: Vec<u8>,
+    escape_counts: Vec<usize>,
+    queue: VecDeque<usize>,
+    fill_position: usize,
+    /// The pixels of an untraced area left to fill in, or to compute if it
+    /// can't be filled in, and the escape count of its border.
+    filling: Vec<usize>,
+    fillable: bool,
+    border: usize,
+}
+
+impl BoundaryTrace {
+    pub fn new(region: RectRegion) -> Self {
+        let len = (region.w * region.h) as usize;
+        let mut trace = Self {
+            flags: vec![0; len],
+            escape_counts: vec![0; len],
+            queue: VecDeque::new(),
+            fill_position: 0,
+            filling: Vec::new(),
+            fillable: false,
+            border: 0,
+            region,
+        };
+
+        let (w, h) = (trace.region.w as usize, trace.region.h as usize);
+        for x in 0..w {
+            trace.enqueue(x);
+            trace.enqueue(x + (h.saturating_sub(1)) * w);
+        }
+        for y in 0..h {
+            trace.enqueue(y * w);
+            trace.enqueue(w.saturating_sub(1) + y * w);
+        }
+
+        trace
+    }
+
+    pub fn region(&self) -> &RectRegion {
+        &self.region
+    }
+
+    /// Give up the trace, returning the region so that it can be traced again
+    /// from scratch.
+    pub fn into_region(self) -> RectRegion {
+        self.region
+    }
+
+    /// An upper bound on the number of pixels in the region that are yet to
+    /// be computed or filled in.
+    pub fn remaining(&self) -> usize {
+        self.flags.len() - self.fill_position
+    }
+
+    fn enqueue(&mut self, i: usize) {
+        if i < self.flags.len() && self.flags[i] & QUEUED == 0 {
+            self.flags[i] |= QUEUED;
+            self.queue.push_back(i);
+        }
+    }
+
+    fn load(&mut self, pixels: &mut impl Pixels, i: usize, work: &mut usize) -> usize {
+        if self.flags[i] & LOADED == 0 {
+            let w = self.region.w as usize;
+            let escape_count = pixels.compute(
+                self.region.x0 + (i % w) as i32,
+                self.region.y0 + (i / w) as i32,
+            );
+            self.escape_counts[i] = escape_count;
+            self.flags[i] |= LOADED;
+            *work += escape_count;
+        }
+        self.escape_counts[i]
+    }
+
+    /// Load the pixel at `i` and its neighbors, and queue the neighbors that
+    /// lie across a contour from it.
+    fn scan(&mut self, pixels: &mut impl Pixels, i: usize, work: &mut usize) {
+        let w = self.region.w as usize;
+        let h = self.region.h as usize;
+        let (x, y) = (i % w, i / w);
+        let center = self.load(pixels, i, work);
+
+        let has_left = x > 0;
+        let has_right = x + 1 < w;
+        let has_up = y > 0;
+        let has_down = y + 1 < h;
+
+        let left = has_left && self.load(pixels, i - 1, work) != center;
+        let right = has_right && self.load(pixels, i + 1, work) != center;
+        let up = has_up && self.load(pixels, i - w, work) != center;
+        let down = has_down && self.load(pixels, i + w, work) != center;
+
+        if left {
+            self.enqueue(i - 1);
+        }
+        if right {
+            self.enqueue(i + 1);
+        }
+        if up {
+            self.enqueue(i - w);
+        }
+        if down {
+            self.enqueue(i + w);
+        }
+
+        // Contours can also continue diagonally
+        if has_up && has_left && (up || left) {
+            self.enqueue(i - w - 1);
+        }
+        if has_up && has_right && (up || right) {
+            self.enqueue(i - w + 1);
+        }
+        if has_down && has_left && (down || left) {
+            self.enqueue(i + w - 1);
+        }
+        if has_down && has_right && (down || right) {
+            self.enqueue(i + w + 1);
+        }
+    }
+
+    /// The untraced area of pixels that are not loaded around `start`, the
+    /// escape count of its border if it is the same all around, and its
+    /// width and height.
+    fn untraced_area(&self, start: usize) -> (Vec<usize>, Option<usize>, usize, usize) {
+        let w = self.region.w as usize;
+        let h = self.region.h as usize;
+        let (mut x0, mut y0, mut x1, mut y1) = (start % w, start / w, start % w, start / w);
+        let mut border = None;
+        let mut uniform = true;
+
+        let mut area = vec![start];
+        let mut visited = HashSet::from([start]);
+        let mut next = 0;
+        while next < area.len() {
+            let i = area[next];
+            next += 1;
+            let (x, y) = (i % w, i / w);
+            (x0, y0, x1, y1) = (x0.min(x), y0.min(y), x1.max(x), y1.max(y));
+
+            let neighbors = [
+                (x > 0).then(|| i - 1),
+                (x + 1 < w).then(|| i + 1),
+                (y > 0).then(|| i - w),
+                (y + 1 < h).then(|| i + w),
+            ];
+            for j in neighbors.into_iter().flatten() {
+                if self.flags[j] & LOADED != 0 {
+                    uniform &=
+                        *border.get_or_insert(self.escape_counts[j]) == self.escape_counts[j];
+                } else if visited.insert(j) {
+                    area.push(j);
+                }
+            }
+        }
+
+        (area, border.filter(|_| uniform), x1 - x0 + 1, y1 - y0 + 1)
+    }
+
+    /// Continue the trace until it is finished or has done more than
+    /// `work_limit` work. Returns the work done and whether the trace is
+    /// finished.
+    pub fn step(&mut self, pixels: &mut impl Pixels, work_limit: usize) -> (usize, bool) {
+        let mut work = 0;
+        let w = self.region.w as usize;
+
+        loop {
+            while let Some(i) = self.queue.pop_front() {
+                self.scan(pixels, i, &mut work);
+                if work > work_limit {
+                    return (work, false);
+                }
+            }
+
+            while let Some(i) = self.filling.pop() {
+                if self.fillable {
+                    pixels.fill(
+                        self.region.x0 + (i % w) as i32,
+                        self.region.y0 + (i / w) as i32,
+                        self.border,
+                    );
+                    self.escape_counts[i] = self.border;
+                    self.flags[i] |= LOADED;
+                    work += 1;
+                } else if self.load(pixels, i, &mut work) != self.border {
+                    // An island whose contour was never reached. Trace it, so
+                    // that any area inside it can be filled in.
+                    self.filling.clear();
+                    self.enqueue(i);
+                }
+                if work > work_limit {
+                    return (work, false);
+                }
+            }
+            if !self.queue.is_empty() {
+                continue;
+            }
+
+            while self.fill_position < self.flags.len()
+                && self.flags[self.fill_position] & LOADED != 0
+            {
+                self.fill_position += 1;
+            }
+            if self.fill_position == self.flags.len() {
+                return (work, true);
+            }
+
+            let (area, border, width, height) = self.untraced_area(self.fill_position);
+            if let Some(border) = border {
+                self.fillable = pixels.fillable(border, width, height);
+                self.border = border;
+                self.filling = area;
+            } else {
+                // A contour inside the area was never reached, so trace
+                // inwards from its edges
+                for i in area {
+                    let (x, y) = (i % w, i / w);
+                    let on_edge = (x > 0 && self.flags[i - 1] & LOADED != 0)
+                        || (x + 1 < w && self.flags[i + 1] & LOADED != 0)
+                        || (y > 0 && self.flags[i - w] & LOADED != 0)
+                        || (i + w < self.flags.len() && self.flags[i + w] & LOADED != 0);
+                    if on_edge {
+                        self.enqueue(i);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BoundaryTrace;
+    use super::Pixels;
+    use crate::rect::RectRegion;
+
+    /// Nested squares of equal value around the top left corner of a 40x30
+    /// image, with a square across several of them whose top row differs from
+    /// the rest.
+    struct Squares {
+        computed: usize,
+        values: Vec<usize>,
+    }
+
+    impl Squares {
+        const W: i32 = 40;
+        const H: i32 = 30;
+
+        fn new() -> Self {
+            Self {
+                computed: 0,
+                values: vec![usize::MAX; (Self::W * Self::H) as usize],
+            }
+        }
+
+        fn value(x: i32, y: i32) -> usize {
+            if (20..30).contains(&x) && (5..15).contains(&y) {
+                if y == 5 {
+                    9
+                } else {
+                    10
+                }
+            } else {
+                (x.max(y) / 4) as usize
+            }
+        }
+    }
+
+    impl Pixels for Squares {
+        fn compute(&mut self, x: i32, y: i32) -> usize {
+            self.computed += 1;
+            let value = Self::value(x, y);
+            self.values[(x + y * Self::W) as usize] = value;
+            value
+        }
+
+        fn fillable(&self, _escape_count: usize, _width: usize, _height: usize) -> bool {
+            true
+        }
+
+        fn fill(&mut self, x: i32, y: i32, escape_count: usize) {
+            self.values[(x + y * Self::W) as usize] = escape_count;
+        }
+    }
+
+    #[test]
+    fn fills_enclosed_areas() {
+        let mut pixels = Squares::new();
+        let mut trace = BoundaryTrace::new(RectRegion::new(0, 0, Squares::W, Squares::H));
+        let (_, finished) = trace.step(&mut pixels, usize::MAX);
+        assert!(finished);
+
+        for y in 0..Squares::H {
+            for x in 0..Squares::W {
+                assert_eq!(
+                    pixels.values[(x + y * Squares::W) as usize],
+                    Squares::value(x, y),
+                    "({x}, {y})"
+                );
+            }
+        }
+        assert!(pixels.computed < pixels.values.len());
+    }
+
+    /// A band of escape count 0 around an island of 1 that is not inside
+    /// the set, and a disc of the set, escape count 2, within a ring of 1
+    /// that is in contact with neither.
+    #[derive(Default)]
+    struct Islands {
+        computed: usize,
+    }
+
+    impl Islands {
+        fn value(x: i32, y: i32) -> usize {
+            let ring = (x - 25).pow(2) + (y - 10).pow(2);
+            if (10..12).contains(&x) && (10..12).contains(&y) {
+                1
+            } else if ring <= 9 {
+                2
+            } else if ring <= 25 {
+                1
+            } else {
+                0
+            }
+        }
+    }
+
+    impl Pixels for Islands {
+        fn compute(&mut self, x: i32, y: i32) -> usize {
+            self.computed += 1;
+            Self::value(x, y)
+        }
+
+        fn fillable(&self, escape_count: usize, _width: usize, _height: usize) -> bool {
+            escape_count == 2
+        }
+
+        fn fill(&mut self, _x: i32, _y: i32, _escape_count: usize) {}
+    }
+
+    #[test]
+    fn finds_isolated_islands() {
+        let region = RectRegion::new(0, 0, 40, 20);
+        let mut trace = BoundaryTrace::new(region);
+        let mut pixels = Islands::default();
+        assert!(trace.step(&mut pixels, usize::MAX).1);
+
+        for y in 0..20 {
+            for x in 0..40 {
+                assert_eq!(
+                    trace.escape_counts[(x + y * 40) as usize],
+                    Islands::value(x, y),
+                    "({x}, {y})"
+                );
+            }
+        }
+        // Only the disc inside the set is filled in
+        assert!(pixels.computed < 40 * 20);
+    }
+
+    #[test]
+    fn resumes_after_work_limit() {
+        let mut pixels = Squares::new();
+        let mut trace = BoundaryTrace::new(RectRegion::new(0, 0, Squares::W, Squares::H));
+        let mut steps = 0;
+        while !trace.step(&mut pixels, 10).1 {
+            steps += 1;
+        }
+        assert!(steps > 1);
+        assert!(pixels.values.iter().all(|v| *v != usize::MAX));
+    }
+}
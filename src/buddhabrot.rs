@@ -0,0 +1,400 @@
+use rand::rngs::StdRng;
+use rand::Rng;
+use rand::SeedableRng;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::complex::Complex;
+use crate::Color;
+use crate::Palette;
+
+/// Region of the complex plane that sample points are drawn from. Orbits of
+/// points outside the view can still pass through it, so this is independent
+/// of the current view.
+const SAMPLE_RE: (f64, f64) = (-2.0, 1.0);
+const SAMPLE_IM: (f64, f64) = (-1.5, 1.5);
+
+/// How often to propose a point from anywhere in the sample region, instead
+/// of one near the current point, so that sampling doesn't get stuck around
+/// one group of orbits.
+const UNIFORM_PROPOSAL_PROBABILITY: f64 = 0.2;
+
+/// Smallest and largest distance to move the current point by, relative to
+/// the size of the view.
+const MUTATION_RADIUS: (f64, f64) = (1e-4, 0.1);
+
+const ESCAPE_ABS_SQUARED: f64 = 4.0;
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub enum RenderMode {
+    /// Color each pixel by how many iterations it takes to escape.
+    #[default]
+    EscapeTime,
+
+    /// Color each pixel by how many escaping orbits pass through it, mapped
+    /// through the gradient.
+    Buddhabrot,
+
+    /// Like [RenderMode::Buddhabrot], but with a separate iteration limit for
+    /// each of the red, green and blue channels.
+    Nebulabrot {
+        red: usize,
+        green: usize,
+        blue: usize,
+    },
+}
+
+impl RenderMode {
+    pub fn is_accumulative(&self) -> bool {
+        !matches!(self, Self::EscapeTime)
+    }
+
+    pub fn nebulabrot_default() -> Self {
+        Self::Nebulabrot {
+            red: 5000,
+            green: 500,
+            blue: 50,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Channel {
+    iteration_limit: usize,
+    hits: Vec<f64>,
+    max_hits: f64,
+}
+
+impl Channel {
+    fn new(iteration_limit: usize, len: usize) -> Self {
+        Self {
+            iteration_limit,
+            hits: vec![0.0; len],
+            max_hits: 0.0,
+        }
+    }
+
+    /// Normalized density in `[0, 1]` at pixel `i`.
+    fn density(&self, i: usize) -> f64 {
+        if self.max_hits == 0.0 {
+            0.0
+        } else {
+            (self.hits[i] / self.max_hits).sqrt()
+        }
+    }
+}
+
+/// An escaping orbit, as the pixels it passes through.
+#[derive(Debug)]
+struct Sample {
+    c: Complex<f64>,
+    escape_count: usize,
+    pixels: Vec<usize>,
+}
+
+impl Sample {
+    fn new() -> Self {
+        Self {
+            c: Complex::from((0.0, 0.0)),
+            escape_count: 0,
+            pixels: Vec::new(),
+        }
+    }
+}
+
+/// Progressive density accumulator for the Buddhabrot family of renderings.
+///
+/// Unlike the escape-time renderer this never has a "finished" image: every
+/// call to [Buddhabrot::compute] adds more samples to the histogram, until
+/// `sample_limit` samples have been taken.
+///
+/// When zoomed in, few orbits from the whole sample region pass through the
+/// view, so points are sampled with the Metropolis-Hastings algorithm: mostly
+/// by moving a little from the current point, and keeping the move in
+/// proportion to how many more pixels of the view the new orbit passes
+/// through. Each orbit then counts inversely to that number of pixels, so
+/// that the histogram still converges to the density of all orbits.
+#[derive(Debug)]
+pub struct Buddhabrot {
+    width: usize,
+    height: usize,
+    top_left: Complex<f64>,
+    btm_right: Complex<f64>,
+    channels: Vec<Channel>,
+    orbit: Vec<Complex<f64>>,
+    current: Sample,
+    proposal: Sample,
+    rng: StdRng,
+    samples: usize,
+    sample_limit: usize,
+}
+
+impl Buddhabrot {
+    pub const SAMPLES_PER_PIXEL: usize = 64;
+
+    pub fn new(
+        mode: &RenderMode,
+        iteration_limit: usize,
+        width: usize,
+        height: usize,
+        top_left: Complex<f64>,
+        btm_right: Complex<f64>,
+    ) -> Self {
+        let len = width * height;
+        let channels = match mode {
+            RenderMode::Nebulabrot { red, green, blue } => vec![
+                Channel::new(*red, len),
+                Channel::new(*green, len),
+                Channel::new(*blue, len),
+            ],
+            RenderMode::EscapeTime | RenderMode::Buddhabrot => {
+                vec![Channel::new(iteration_limit, len)]
+            }
+        };
+
+        Self {
+            width,
+            height,
+            top_left,
+            btm_right,
+            channels,
+            orbit: Vec::new(),
+            current: Sample::new(),
+            proposal: Sample::new(),
+            rng: StdRng::seed_from_u64(0),
+            samples: 0,
+            sample_limit: len * Self::SAMPLES_PER_PIXEL,
+        }
+    }
+
+    fn max_iteration_limit(&self) -> usize {
+        self.channels
+            .iter()
+            .map(|ch| ch.iteration_limit)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Points in the main cardioid and the period-2 bulb never escape, so
+    /// there is no need to iterate them.
+    fn is_known_interior(c: Complex<f64>) -> bool {
+        let im2 = c.im * c.im;
+        let q = (c.re - 0.25) * (c.re - 0.25) + im2;
+        let in_cardioid = q * (q + (c.re - 0.25)) <= 0.25 * im2;
+        let in_bulb = (c.re + 1.0) * (c.re + 1.0) + im2 <= 0.0625;
+        in_cardioid || in_bulb
+    }
+
+    fn pixel_index(&self, z: &Complex<f64>) -> Option<usize> {
+        let span = self.btm_right - self.top_left;
+        let x = ((z.re - self.top_left.re) / span.re * self.width as f64).floor();
+        let y = ((z.im - self.top_left.im) / span.im * self.height as f64).floor();
+        if x >= 0.0 && x < self.width as f64 && y >= 0.0 && y < self.height as f64 {
+            Some(x as usize + y as usize * self.width)
+        } else {
+            None
+        }
+    }
+
+    /// Fraction of `sample_limit` that has been taken, in `[0, 1]`.
+    pub fn progress(&self) -> f64 {
+        if self.sample_limit == 0 {
+            1.0
+        } else {
+            self.samples as f64 / self.sample_limit as f64
+        }
+    }
+
+    /// A point near `c`, at a distance spread evenly in scale between the
+    /// bounds of [MUTATION_RADIUS].
+    fn mutate(&mut self, c: Complex<f64>) -> Complex<f64> {
+        let size = (self.btm_right.re - self.top_left.re).abs();
+        let (low, high) = (MUTATION_RADIUS.0 * size, MUTATION_RADIUS.1 * size);
+        let radius = high * (low / high).powf(self.rng.gen::<f64>());
+        let angle = self.rng.gen_range(0.0..std::f64::consts::TAU);
+        c + Complex::from((radius * angle.cos(), radius * angle.sin()))
+    }
+
+    /// Iterate `c` into [Buddhabrot::proposal], and return the number of
+    /// iterations performed.
+    fn trace(&mut self, c: Complex<f64>, max_iterations: usize) -> usize {
+        self.proposal.c = c;
+        self.proposal.pixels.clear();
+        if Self::is_known_interior(c) {
+            return 0;
+        }
+
+        self.orbit.clear();
+        let mut z = c;
+        let mut escape_count = None;
+        for i in 0..max_iterations {
+            let (abs, z2) = z.abs_squared_and_square();
+            if abs >= ESCAPE_ABS_SQUARED {
+                escape_count = Some(i);
+                break;
+            }
+            self.orbit.push(z);
+            z = z2 + c;
+        }
+
+        if let Some(escape_count) = escape_count {
+            self.proposal.escape_count = escape_count;
+            for k in 0..self.orbit.len() {
+                if let Some(i) = self.pixel_index(&self.orbit[k]) {
+                    self.proposal.pixels.push(i);
+                }
+            }
+        }
+        self.orbit.len() + 1
+    }
+
+    /// Take samples until roughly `work_limit` iterations have been performed,
+    /// and return the number of iterations performed.
+    pub fn compute(&mut self, work_limit: usize) -> usize {
+        let max_iterations = self.max_iteration_limit();
+        let mut total_work = 0;
+
+        while self.samples < self.sample_limit && total_work <= work_limit {
+            self.samples += 1;
+
+            let c = if self.current.pixels.is_empty() {
+                // Until an orbit passes through the view, start from points
+                // in it, which pass through at least their own pixel
+                Complex::from((
+                    self.rng.gen_range(self.top_left.re..self.btm_right.re),
+                    self.rng.gen_range(self.btm_right.im..self.top_left.im),
+                ))
+            } else if self.rng.gen_bool(UNIFORM_PROPOSAL_PROBABILITY) {
+                Complex::from((
+                    self.rng.gen_range(SAMPLE_RE.0..SAMPLE_RE.1),
+                    self.rng.gen_range(SAMPLE_IM.0..SAMPLE_IM.1),
+                ))
+            } else {
+                self.mutate(self.current.c)
+            };
+            total_work += self.trace(c, max_iterations);
+
+            let current = self.current.pixels.len() as f64;
+            let proposed = self.proposal.pixels.len() as f64;
+            if current == 0.0 || self.rng.gen::<f64>() * current < proposed {
+                std::mem::swap(&mut self.current, &mut self.proposal);
+            }
+
+            let weight = 1.0 / self.current.pixels.len() as f64;
+            for &i in &self.current.pixels {
+                for ch in &mut self.channels {
+                    if self.current.escape_count < ch.iteration_limit {
+                        ch.hits[i] += weight;
+                        ch.max_hits = ch.max_hits.max(ch.hits[i]);
+                    }
+                }
+            }
+        }
+
+        total_work
+    }
+
+    /// Write the accumulated densities as RGBA into `pixels`. A single channel
+    /// is mapped through `palette`; three channels are mapped directly to red,
+    /// green and blue.
+    pub fn render_pixels(&self, pixels: &mut [u8], palette: &Palette, iteration_limit: usize) {
+        for i in 0..(self.width * self.height) {
+            let color = match self.channels.as_slice() {
+                [r, g, b] => Color::of(
+                    (r.density(i) * 255.0).round() as u8,
+                    (g.density(i) * 255.0).round() as u8,
+                    (b.density(i) * 255.0).round() as u8,
+                    255,
+                ),
+                [ch] => {
+                    let value = (ch.density(i) * iteration_limit.saturating_sub(1) as f64).round();
+                    *palette.get_color(value as usize, iteration_limit)
+                }
+                _ => Color::of(0, 0, 0, 255),
+            };
+            let pixel_index = i * 4;
+            pixels[pixel_index] = color.r;
+            pixels[pixel_index + 1] = color.g;
+            pixels[pixel_index + 2] = color.b;
+            pixels[pixel_index + 3] = color.a;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Buddhabrot;
+    use super::RenderMode;
+    use crate::complex::Complex;
+
+    fn full_view(mode: &RenderMode, size: usize) -> Buddhabrot {
+        Buddhabrot::new(
+            mode,
+            100,
+            size,
+            size,
+            Complex::from((-2.0, 1.5)),
+            Complex::from((1.0, -1.5)),
+        )
+    }
+
+    #[test]
+    fn known_interior() {
+        assert!(Buddhabrot::is_known_interior(Complex::from((0.0, 0.0))));
+        assert!(Buddhabrot::is_known_interior(Complex::from((-1.0, 0.0))));
+        assert!(!Buddhabrot::is_known_interior(Complex::from((0.5, 0.5))));
+        assert!(!Buddhabrot::is_known_interior(Complex::from((-2.0, 0.0))));
+    }
+
+    #[test]
+    fn compute_respects_sample_limit() {
+        let mut b = full_view(&RenderMode::Buddhabrot, 4);
+        let mut total = 0;
+        loop {
+            let work = b.compute(1000);
+            total += work;
+            if work <= 1000 {
+                break;
+            }
+        }
+        assert!(total > 0);
+        assert_eq!(b.samples, b.sample_limit);
+        assert_eq!(b.compute(1000), 0);
+    }
+
+    #[test]
+    fn nebulabrot_bands_are_nested() {
+        let mut b = full_view(
+            &RenderMode::Nebulabrot {
+                red: 200,
+                green: 50,
+                blue: 10,
+            },
+            16,
+        );
+        b.compute(usize::MAX);
+
+        let total = |ch: usize| b.channels[ch].hits.iter().sum::<f64>();
+        assert!(total(0) >= total(1));
+        assert!(total(1) >= total(2));
+        assert!(total(2) > 0.0);
+    }
+
+    #[test]
+    fn zoomed_views_get_hits() {
+        // A view a thousandth the size of the sample region, on the boundary
+        let (re, im, radius) = (-0.7453, 0.1127, 0.0015);
+        let mut b = Buddhabrot::new(
+            &RenderMode::Buddhabrot,
+            1000,
+            16,
+            16,
+            Complex::from((re - radius, im + radius)),
+            Complex::from((re + radius, im - radius)),
+        );
+        b.compute(usize::MAX);
+
+        let lit = b.channels[0].hits.iter().filter(|h| **h > 0.0).count();
+        assert!(lit > 16 * 16 / 2, "{lit}");
+    }
+}
@@ -0,0 +1,110 @@
+//! Time-budgeted computation: [crate::Engine::compute_for] works in chunks
+//! whose size adapts to how fast the chunks before it were computed.
+
+use std::time::Duration;
+use std::time::Instant;
+
+/// A monotonic source of time. Only differences between readings are
+/// meaningful.
+pub trait Clock {
+    fn now(&self) -> Duration;
+}
+
+/// Time elapsed since the instant, for native code.
+impl Clock for Instant {
+    fn now(&self) -> Duration {
+        self.elapsed()
+    }
+}
+
+/// Time elapsed since the page was loaded, for code running in a browser.
+#[cfg(feature = "web")]
+impl Clock for web_sys::Performance {
+    fn now(&self) -> Duration {
+        Duration::from_secs_f64(web_sys::Performance::now(self) / 1000_f64)
+    }
+}
+
+/// The amount of work to do in one chunk, adjusted to fit a time budget.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FrameBudget {
+    work_limit: f64,
+}
+
+impl Default for FrameBudget {
+    fn default() -> Self {
+        Self {
+            work_limit: Self::INITIAL_WORK_LIMIT,
+        }
+    }
+}
+
+impl FrameBudget {
+    const INITIAL_WORK_LIMIT: f64 = 100000_f64;
+
+    /// Factor by which the work limit shrinks or grows.
+    const STEP: f64 = 1.5;
+
+    /// Fraction of the budget below which the work limit grows, so that
+    /// growing doesn't immediately overshoot the budget.
+    const GROW_BELOW: f64 = 0.6;
+
+    pub fn work_limit(&self) -> usize {
+        self.work_limit.round() as usize
+    }
+
+    /// Adjust the work limit after a chunk of `work` took `elapsed` time.
+    pub fn update(&mut self, budget: Duration, elapsed: Duration, work: usize) {
+        if elapsed > budget {
+            self.work_limit = (self.work_limit / Self::STEP).max(1_f64);
+        } else if elapsed.as_secs_f64() < budget.as_secs_f64() * Self::GROW_BELOW
+            && work as f64 >= self.work_limit
+        {
+            self.work_limit *= Self::STEP;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::FrameBudget;
+
+    const BUDGET: Duration = Duration::from_millis(16);
+
+    #[test]
+    fn shrinks_when_over_budget() {
+        let mut budget = FrameBudget::default();
+        let limit = budget.work_limit();
+        budget.update(BUDGET, Duration::from_millis(20), limit);
+        assert!(budget.work_limit() < limit);
+    }
+
+    #[test]
+    fn grows_only_when_limit_was_used() {
+        let mut budget = FrameBudget::default();
+        let limit = budget.work_limit();
+        budget.update(BUDGET, Duration::from_millis(1), limit / 2);
+        assert_eq!(budget.work_limit(), limit);
+        budget.update(BUDGET, Duration::from_millis(1), limit);
+        assert!(budget.work_limit() > limit);
+    }
+
+    #[test]
+    fn holds_steady_near_budget() {
+        let mut budget = FrameBudget::default();
+        let limit = budget.work_limit();
+        budget.update(BUDGET, Duration::from_millis(12), limit);
+        assert_eq!(budget.work_limit(), limit);
+    }
+
+    #[test]
+    fn never_reaches_zero() {
+        let mut budget = FrameBudget::default();
+        for _ in 0..100 {
+            budget.update(BUDGET, Duration::from_secs(1), 1);
+        }
+        assert_eq!(budget.work_limit(), 1);
+    }
+}
@@ -0,0 +1,351 @@
+//! Interpolating between gradient pivots in different color spaces.
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::Color;
+
+/// The color space in which [crate::Gradient] interpolates between pivots.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub enum InterpolationSpace {
+    /// Gamma-encoded sRGB channels, as in CSS gradients.
+    #[default]
+    Srgb,
+
+    /// Linear light RGB channels, which keeps midpoints bright.
+    LinearRgb,
+
+    /// Hue, saturation and value, taking the shorter way around the hue
+    /// circle.
+    Hsv,
+
+    /// Hue, saturation and lightness, taking the shorter way around the hue
+    /// circle.
+    Hsl,
+
+    /// The perceptually uniform OKLab space.
+    Oklab,
+
+    /// OKLab in polar coordinates of lightness, chroma and hue, taking the
+    /// shorter way around the hue circle.
+    Oklch,
+}
+
+impl InterpolationSpace {
+    pub const ALL: [Self; 6] = [
+        Self::Srgb,
+        Self::LinearRgb,
+        Self::Hsv,
+        Self::Hsl,
+        Self::Oklab,
+        Self::Oklch,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Srgb => "sRGB",
+            Self::LinearRgb => "Linear RGB",
+            Self::Hsv => "HSV",
+            Self::Hsl => "HSL",
+            Self::Oklab => "OKLab",
+            Self::Oklch => "OKLCH",
+        }
+    }
+
+    /// The color at `target_value` between `a` at `a_value` and `b` at
+    /// `b_value`, like [Color::lerp] but in this space.
+    pub fn interpolate(
+        &self,
+        a: &Color,
+        b: &Color,
+        a_value: isize,
+        b_value: isize,
+        target_value: isize,
+    ) -> Color {
+        if *self == Self::Srgb {
+            return Color::lerp(a, b, a_value, b_value, target_value);
+        }
+
+        let t = (target_value - a_value) as f64 / (b_value - a_value) as f64;
+        self.interpolate_at(a, b, t)
+    }
+
+    /// The color at fraction `t` of the way from `a` to `b` in this space.
+    pub fn interpolate_at(&self, a: &Color, b: &Color, t: f64) -> Color {
+        let [ca, cb] = [a, b].map(|color| self.encode_color(color));
+        let mixed = if let Some((hue, chroma)) = self.polar_channels() {
+            mix_polar(ca, cb, hue, chroma, t)
+        } else {
+            [0, 1, 2, 3].map(|i| ca[i] + (cb[i] - ca[i]) * t)
+        };
+        self.decode_color(mixed)
+    }
+
+    /// Channels of `colors` in this space, with alpha last, for interpolating
+    /// along all of them at once. Hues are unwrapped so that each differs from
+    /// the one before by at most half a turn, and gray colors take the hue of
+    /// a neighbour.
+    pub(crate) fn encode_sequence(&self, colors: &[Color]) -> Vec<[f64; 4]> {
+        let mut channels: Vec<[f64; 4]> = colors.iter().map(|c| self.encode_color(c)).collect();
+        if let Some((hue, chroma)) = self.polar_channels() {
+            let mut known = channels.iter().find(|c| c[chroma] != 0.0).map(|c| c[hue]);
+            let mut previous: Option<f64> = None;
+            for c in &mut channels {
+                if c[chroma] == 0.0 {
+                    c[hue] = known.unwrap_or_default();
+                } else {
+                    known = Some(c[hue]);
+                }
+                if let Some(previous) = previous {
+                    c[hue] = previous + (c[hue] - previous + 180.0).rem_euclid(360.0) - 180.0;
+                }
+                previous = Some(c[hue]);
+            }
+        }
+        channels
+    }
+
+    /// The color with `channels` in this space, with alpha last.
+    pub(crate) fn decode_color(&self, channels: [f64; 4]) -> Color {
+        let [r, g, b] = self.decode([channels[0], channels[1], channels[2]]);
+        let to_u8 = |f: f64| (f.clamp(0.0, 1.0) * 255.0).round() as u8;
+        Color::of(
+            to_u8(r),
+            to_u8(g),
+            to_u8(b),
+            channels[3].clamp(0.0, 255.0).round() as u8,
+        )
+    }
+
+    fn encode_color(&self, color: &Color) -> [f64; 4] {
+        let [x, y, z] = self.encode(srgb_channels(color));
+        [x, y, z, f64::from(color.a)]
+    }
+
+    /// The indices of the hue and chroma channels, if this space has a hue.
+    fn polar_channels(&self) -> Option<(usize, usize)> {
+        match self {
+            Self::Hsv | Self::Hsl => Some((0, 1)),
+            Self::Oklch => Some((2, 1)),
+            _ => None,
+        }
+    }
+
+    /// Channels in this space of a color with sRGB channels in 0..=1.
+    fn encode(&self, rgb: [f64; 3]) -> [f64; 3] {
+        match self {
+            Self::Srgb => rgb,
+            Self::LinearRgb => rgb.map(srgb_to_linear),
+            Self::Hsv => rgb_to_hsv(rgb),
+            Self::Hsl => rgb_to_hsl(rgb),
+            Self::Oklab => linear_to_oklab(rgb.map(srgb_to_linear)),
+            Self::Oklch => oklab_to_oklch(linear_to_oklab(rgb.map(srgb_to_linear))),
+        }
+    }
+
+    /// sRGB channels in 0..=1 of a color with channels in this space.
+    fn decode(&self, channels: [f64; 3]) -> [f64; 3] {
+        match self {
+            Self::Srgb => channels,
+            Self::LinearRgb => channels.map(linear_to_srgb),
+            Self::Hsv => hsv_to_rgb(channels),
+            Self::Hsl => hsl_to_rgb(channels),
+            Self::Oklab => oklab_to_linear(channels).map(linear_to_srgb),
+            Self::Oklch => oklab_to_linear(oklch_to_oklab(channels)).map(linear_to_srgb),
+        }
+    }
+}
+
+fn srgb_channels(color: &Color) -> [f64; 3] {
+    [color.r, color.g, color.b].map(|c| f64::from(c) / 255.0)
+}
+
+/// Interpolate channels where channel `hue` is an angle in degrees, taking
+/// the shorter way around. A hue is meaningless where channel `chroma` is
+/// zero, so then the other color's hue is used throughout.
+fn mix_polar(a: [f64; 4], b: [f64; 4], hue: usize, chroma: usize, t: f64) -> [f64; 4] {
+    let (mut a, mut b) = (a, b);
+    if a[chroma] == 0.0 {
+        a[hue] = b[hue];
+    } else if b[chroma] == 0.0 {
+        b[hue] = a[hue];
+    }
+    let hue_difference = (b[hue] - a[hue] + 180.0).rem_euclid(360.0) - 180.0;
+
+    let mut mixed = [0, 1, 2, 3].map(|i| a[i] + (b[i] - a[i]) * t);
+    mixed[hue] = (a[hue] + hue_difference * t).rem_euclid(360.0);
+    mixed
+}
+
+fn srgb_to_linear(c: f64) -> f64 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(c: f64) -> f64 {
+    if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.max(0.0).powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Hue in degrees, and chroma, max and min of the channels.
+fn hue_chroma([r, g, b]: [f64; 3]) -> (f64, f64, f64, f64) {
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let chroma = max - min;
+    let hue = if chroma == 0.0 {
+        0.0
+    } else if max == r {
+        60.0 * ((g - b) / chroma).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / chroma + 2.0)
+    } else {
+        60.0 * ((r - g) / chroma + 4.0)
+    };
+    (hue, chroma, max, min)
+}
+
+/// RGB from hue in degrees, chroma, and the amount added to every channel.
+fn rgb_from_hue_chroma(hue: f64, chroma: f64, m: f64) -> [f64; 3] {
+    let h = hue.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (h % 2.0 - 1.0).abs());
+    let [r, g, b] = match h as u8 {
+        0 => [chroma, x, 0.0],
+        1 => [x, chroma, 0.0],
+        2 => [0.0, chroma, x],
+        3 => [0.0, x, chroma],
+        4 => [x, 0.0, chroma],
+        _ => [chroma, 0.0, x],
+    };
+    [r + m, g + m, b + m]
+}
+
+fn rgb_to_hsv(rgb: [f64; 3]) -> [f64; 3] {
+    let (hue, chroma, max, _) = hue_chroma(rgb);
+    let saturation = if max == 0.0 { 0.0 } else { chroma / max };
+    [hue, saturation, max]
+}
+
+fn hsv_to_rgb([h, s, v]: [f64; 3]) -> [f64; 3] {
+    let chroma = v * s;
+    rgb_from_hue_chroma(h, chroma, v - chroma)
+}
+
+fn rgb_to_hsl(rgb: [f64; 3]) -> [f64; 3] {
+    let (hue, chroma, max, min) = hue_chroma(rgb);
+    let lightness = (max + min) / 2.0;
+    let saturation = if lightness == 0.0 || lightness == 1.0 {
+        0.0
+    } else {
+        chroma / (1.0 - (2.0 * lightness - 1.0).abs())
+    };
+    [hue, saturation, lightness]
+}
+
+fn hsl_to_rgb([h, s, l]: [f64; 3]) -> [f64; 3] {
+    let chroma = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    rgb_from_hue_chroma(h, chroma, l - chroma / 2.0)
+}
+
+fn linear_to_oklab([r, g, b]: [f64; 3]) -> [f64; 3] {
+    let l = (0.412_221_470_8 * r + 0.536_332_536_3 * g + 0.051_445_992_9 * b).cbrt();
+    let m = (0.211_903_498_2 * r + 0.680_699_545_1 * g + 0.107_396_956_6 * b).cbrt();
+    let s = (0.088_302_461_9 * r + 0.281_718_837_6 * g + 0.629_978_700_5 * b).cbrt();
+    [
+        0.210_454_255_3 * l + 0.793_617_785_0 * m - 0.004_072_046_8 * s,
+        1.977_998_495_1 * l - 2.428_592_205_0 * m + 0.450_593_709_9 * s,
+        0.025_904_037_1 * l + 0.782_771_766_2 * m - 0.808_675_766_0 * s,
+    ]
+}
+
+fn oklab_to_linear([l, a, b]: [f64; 3]) -> [f64; 3] {
+    let l_ = (l + 0.396_337_777_4 * a + 0.215_803_757_3 * b).powi(3);
+    let m_ = (l - 0.105_561_345_8 * a - 0.063_854_172_8 * b).powi(3);
+    let s_ = (l - 0.089_484_177_5 * a - 1.291_485_548_0 * b).powi(3);
+    [
+        4.076_741_662_1 * l_ - 3.307_711_591_3 * m_ + 0.230_969_929_2 * s_,
+        -1.268_438_004_6 * l_ + 2.609_757_401_1 * m_ - 0.341_319_396_5 * s_,
+        -0.004_196_086_3 * l_ - 0.703_418_614_7 * m_ + 1.707_614_701_0 * s_,
+    ]
+}
+
+/// Chroma below which a color counts as gray, whose hue is noise.
+const OKLCH_GRAY: f64 = 1e-4;
+
+fn oklab_to_oklch([l, a, b]: [f64; 3]) -> [f64; 3] {
+    let chroma = a.hypot(b);
+    if chroma < OKLCH_GRAY {
+        [l, 0.0, 0.0]
+    } else {
+        [l, chroma, b.atan2(a).to_degrees().rem_euclid(360.0)]
+    }
+}
+
+fn oklch_to_oklab([l, c, h]: [f64; 3]) -> [f64; 3] {
+    let h = h.to_radians();
+    [l, c * h.cos(), c * h.sin()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::InterpolationSpace;
+    use crate::Color;
+
+    #[test]
+    fn endpoints_round_trip() {
+        let colors = [
+            Color::of(0, 0, 0, 255),
+            Color::of(255, 255, 255, 255),
+            Color::of(255, 0, 0, 255),
+            Color::of(12, 200, 99, 128),
+            Color::of(80, 80, 80, 0),
+        ];
+        for space in InterpolationSpace::ALL {
+            for a in &colors {
+                for b in &colors {
+                    assert_eq!(space.interpolate(a, b, 0, 10, 0), *a, "{space:?}");
+                    assert_eq!(space.interpolate(a, b, 0, 10, 10), *b, "{space:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn midpoints() {
+        let blue = Color::of(0, 0, 255, 255);
+        let yellow = Color::of(255, 255, 0, 255);
+        let red = Color::of(255, 0, 0, 255);
+        let black = Color::of(0, 0, 0, 255);
+        let magenta = Color::of(255, 0, 255, 255);
+        let mid = |space: InterpolationSpace, a, b| space.interpolate(a, b, 0, 2, 1);
+
+        // Complementary colors meet in gray in sRGB, but not in OKLCH
+        assert_eq!(
+            mid(InterpolationSpace::Srgb, &blue, &yellow),
+            Color::of(127, 127, 128, 255)
+        );
+        let oklch = mid(InterpolationSpace::Oklch, &blue, &yellow);
+        assert!(oklch.r.abs_diff(oklch.b) > 64, "{oklch:?}");
+
+        // Linear light midpoints are brighter
+        assert_eq!(
+            mid(InterpolationSpace::LinearRgb, &red, &black),
+            Color::of(188, 0, 0, 255)
+        );
+
+        // Hue takes the short way from red to magenta, not through green
+        let hsv = mid(InterpolationSpace::Hsv, &red, &magenta);
+        assert_eq!(hsv, Color::of(255, 0, 128, 255));
+        assert_eq!(mid(InterpolationSpace::Hsl, &red, &magenta), hsv);
+
+        // Gray takes the hue of the other end rather than red's
+        let gray = Color::of(128, 128, 128, 255);
+        let hsv = mid(InterpolationSpace::Hsv, &gray, &blue);
+        assert!(hsv.b > hsv.r && hsv.r == hsv.g, "{hsv:?}");
+    }
+}
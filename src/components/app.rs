@@ -11,9 +11,11 @@ use yew::Html;
 use crate::components::canvas::Canvas;
 use crate::components::controls::Controls;
 use crate::components::github_corner::GithubCorner;
+use crate::components::render_progress::RenderProgress;
 use crate::components::sidebar::Sidebar;
 use crate::crate_info::crate_name;
 use crate::presets::PRESETS;
+use crate::stats::RenderStats;
 use crate::utils::error_println;
 use crate::version::git_version;
 use crate::EngineSettings;
@@ -64,6 +66,9 @@ pub fn App() -> Html {
         }
     });
 
+    let stats = use_state(RenderStats::default);
+    let on_stats = use_callback(|stats, set_stats| set_stats.set(stats), stats.setter());
+
     let on_toggle_sidebar = use_callback(
         |_, sidebar_expanded| sidebar_expanded.set(!**sidebar_expanded),
         sidebar_expanded.clone(),
@@ -83,7 +88,7 @@ pub fn App() -> Html {
                 repo="emlun/fraktal"
                 visible={ *sidebar_expanded }
             />
-            <Canvas settings={ settings.clone() } />
+            <Canvas { on_stats } settings={ settings.clone() } />
             <Sidebar
                 content_classes={ vec!["Sidebar-Content"]}
                 expanded={ *sidebar_expanded }
@@ -91,6 +96,7 @@ pub fn App() -> Html {
                 on_toggle={ on_toggle_sidebar }
                 title="Settings"
             >
+                <RenderProgress stats={ *stats } />
                 <Controls settings={ settings.clone() } />
 
                 <div class={ classes!("flex-stretch") }/>
@@ -2,8 +2,11 @@ use std::rc::Rc;
 
 use wasm_bindgen::prelude::Closure;
 use wasm_bindgen::JsCast;
+use web_sys::window;
 use web_sys::AddEventListenerOptions;
+use web_sys::DragEvent;
 use web_sys::EventListenerOptions;
+use web_sys::FileReader;
 use web_sys::HtmlElement;
 use web_sys::MouseEvent;
 use web_sys::WheelEvent;
@@ -20,7 +23,10 @@ use yew::NodeRef;
 use yew::Properties;
 use yew::UseStateHandle;
 
-use crate::utils::error_log;
+use crate::export::read_png_state;
+use crate::stats::RenderStats;
+use crate::utils::error_println;
+use crate::yew::console::error_log;
 use crate::yew::state::UpdateUseStateHandle;
 use crate::EngineSettings;
 
@@ -32,11 +38,71 @@ pub struct Props {
     #[prop_or(10_f64)]
     pub pan_trigger_threshold: f64,
     pub settings: UseStateHandle<EngineSettings>,
+    pub on_stats: Callback<RenderStats>,
+}
+
+fn on_drag_over(event: DragEvent) {
+    // Allow dropping files
+    event.prevent_default();
+}
+
+/// Restore the settings embedded in a PNG file dropped onto the canvas, by
+/// navigating to them like a preset link does.
+fn on_drop(event: DragEvent) {
+    event.prevent_default();
+    let Some(file) = event
+        .data_transfer()
+        .and_then(|data| data.files())
+        .and_then(|files| files.get(0))
+    else {
+        return;
+    };
+
+    let reader = match FileReader::new() {
+        Ok(reader) => reader,
+        Err(err) => {
+            error_log!("Failed to create file reader", err);
+            return;
+        }
+    };
+
+    let on_load = Closure::once_into_js({
+        let reader = reader.clone();
+        let name = file.name();
+        move || {
+            let bytes = match reader.result() {
+                Ok(result) => js_sys::Uint8Array::new(&result).to_vec(),
+                Err(err) => {
+                    error_log!("Failed to read dropped file", err);
+                    return;
+                }
+            };
+            match read_png_state(bytes.as_slice())
+                .and_then(|state| EngineSettings::try_restore(&state).map(|_| state))
+            {
+                Ok(state) => {
+                    if let Err(err) = window()
+                        .unwrap()
+                        .location()
+                        .set_search(&format!("state={state}"))
+                    {
+                        error_log!("Failed to navigate to dropped settings", err);
+                    }
+                }
+                Err(err) => error_println!("Failed to restore settings from {name}: {err}"),
+            }
+        }
+    });
+    reader.set_onload(Some(on_load.unchecked_ref()));
+    if let Err(err) = reader.read_as_array_buffer(&file) {
+        error_log!("Failed to read dropped file", err);
+    }
 }
 
 #[function_component]
 pub fn CanvasControls(props: &Props) -> Html {
     let mouse_pos = use_mut_ref::<Option<Pos>, _>(|| None);
+    let cursor_pos = use_mut_ref::<Option<Pos>, _>(|| None);
     let scroll_start_pos = use_mut_ref::<Option<Pos>, _>(|| None);
     let wrapper = use_node_ref();
 
@@ -61,6 +127,7 @@ pub fn CanvasControls(props: &Props) -> Html {
         {
             let scroll_start_pos = Rc::clone(&scroll_start_pos);
             let mouse_pos = Rc::clone(&mouse_pos);
+            let cursor_pos = Rc::clone(&cursor_pos);
             move |(get_scroll_offset, settings, wrapper, pan_trigger_threshold): &(
                 Callback<(), Pos>,
                 UseStateHandle<EngineSettings>,
@@ -93,12 +160,33 @@ pub fn CanvasControls(props: &Props) -> Html {
                 let on_mouse_move: Closure<dyn Fn(MouseEvent)> = Closure::new({
                     let scroll_start_pos = Rc::clone(&scroll_start_pos);
                     let mouse_pos = Rc::clone(&mouse_pos);
+                    let cursor_pos = Rc::clone(&cursor_pos);
+                    let pan_trigger_threshold = *pan_trigger_threshold;
+                    let settings = settings.clone();
                     move |event: MouseEvent| {
+                        let pos = Pos {
+                            x: event.offset_x(),
+                            y: event.offset_y(),
+                        };
                         if scroll_start_pos.borrow().is_some() {
-                            *mouse_pos.borrow_mut() = Some(Pos {
-                                x: event.offset_x(),
-                                y: event.offset_y(),
+                            *mouse_pos.borrow_mut() = Some(pos);
+                        } else if settings.get_scheduler().follows_cursor() {
+                            // Only move the focus once the cursor has moved a bit, to
+                            // avoid updating the settings on every event
+                            let moved = cursor_pos.borrow().is_none_or(|cursor_pos| {
+                                Pos {
+                                    x: pos.x - cursor_pos.x,
+                                    y: pos.y - cursor_pos.y,
+                                }
+                                .abs()
+                                    >= pan_trigger_threshold
                             });
+                            if moved {
+                                *cursor_pos.borrow_mut() = Some(pos);
+                                if let (Ok(x), Ok(y)) = (pos.x.try_into(), pos.y.try_into()) {
+                                    settings.update(|s| s.set_cursor(x, y));
+                                }
+                            }
                         }
                     }
                 });
@@ -242,9 +330,12 @@ pub fn CanvasControls(props: &Props) -> Html {
         <div
             ref={ wrapper }
         class={ classes!("Canvas-Controls") }
+            ondragover={ on_drag_over }
+            ondrop={ on_drop }
         >
             <Canvas
                 { get_scroll_offset }
+                on_stats={ props.on_stats.clone() }
                 settings={ props.settings.clone() }
             />
         </div>
@@ -1,8 +1,11 @@
 use std::cell::RefCell;
+use std::fmt::Display;
 use std::num::TryFromIntError;
 use std::rc::Rc;
+use std::time::Duration;
 
 use wasm_bindgen::prelude::Closure;
+use wasm_bindgen::Clamped;
 use wasm_bindgen::JsCast;
 use wasm_bindgen::JsValue;
 use web_sys::window;
@@ -21,9 +24,10 @@ use yew::NodeRef;
 use yew::Properties;
 use yew::UseStateHandle;
 
-use crate::utils::error_log;
+use crate::stats::RenderStats;
 use crate::utils::error_println;
-use crate::utils::PtrEq;
+use crate::yew::console::error_log;
+use crate::yew::ptr_eq::PtrEq;
 use crate::yew::state::UpdateUseStateHandle;
 use crate::Engine;
 use crate::EngineSettings;
@@ -32,10 +36,15 @@ use super::Pos;
 
 type GetScrollOffset = Callback<(), Pos>;
 
+/// Time to spend computing in each animation frame, leaving time to render at
+/// 60 frames per second.
+const FRAME_BUDGET: Duration = Duration::from_micros(16_667);
+
 #[derive(Clone, PartialEq, Properties)]
 pub struct Props {
     pub settings: UseStateHandle<EngineSettings>,
     pub get_scroll_offset: GetScrollOffset,
+    pub on_stats: Callback<RenderStats>,
 }
 
 #[derive(Debug)]
@@ -45,6 +54,16 @@ enum CatchallError {
     NodeRefCast,
 }
 
+impl Display for CatchallError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        match self {
+            Self::TryFromInt(err) => write!(f, "{err}"),
+            Self::JsError(err) => write!(f, "{err:?}"),
+            Self::NodeRefCast => write!(f, "Not a canvas element"),
+        }
+    }
+}
+
 impl From<TryFromIntError> for CatchallError {
     fn from(v: TryFromIntError) -> Self {
         Self::TryFromInt(v)
@@ -93,7 +112,7 @@ pub fn Canvas(props: &Props) -> Html {
             let settings = props.settings.clone();
             move |()| {
                 if let Err(err) = resize_canvas(&canvas_ref, &settings) {
-                    error_println!("Failed to resize canvas: {:?}", err);
+                    error_println!("Failed to resize canvas: {}", err);
                 }
             }
         },
@@ -107,7 +126,7 @@ pub fn Canvas(props: &Props) -> Html {
                 let settings = settings.clone();
                 move || {
                     if let Err(err) = resize_canvas(&canvas_ref, &settings) {
-                        error_println!("Failed to resize canvas: {:?}", err);
+                        error_println!("Failed to resize canvas: {}", err);
                     }
                 }
             });
@@ -128,11 +147,12 @@ pub fn Canvas(props: &Props) -> Html {
 
     use_effect_with_deps(
         {
-            move |(canvas_ref, engine, settings, get_scroll_offset): &(
+            move |(canvas_ref, engine, settings, get_scroll_offset, on_stats): &(
                 NodeRef,
                 PtrEq<RefCell<Engine>>,
                 UseStateHandle<EngineSettings>,
                 GetScrollOffset,
+                Callback<RenderStats>,
             )| {
                 let canvas: HtmlCanvasElement = canvas_ref.clone().cast().unwrap();
                 let stop_render_loop = Rc::new(RefCell::new(None));
@@ -146,7 +166,7 @@ pub fn Canvas(props: &Props) -> Html {
                         let get_scroll_offset = get_scroll_offset.clone();
                         move || {
                             let image_data = ImageData::new_with_u8_clamped_array(
-                                engine.borrow().image_data(),
+                                Clamped(engine.borrow().image_data()),
                                 canvas.width(),
                             )
                             .unwrap();
@@ -158,8 +178,9 @@ pub fn Canvas(props: &Props) -> Html {
                                 .dyn_into()
                                 .unwrap();
 
-                            ctx.set_fill_style(&"#000000".into());
-                            ctx.fill_rect(
+                            // Leave uncovered and transparent areas showing
+                            // the checkerboard behind the canvas
+                            ctx.clear_rect(
                                 0_f64,
                                 0_f64,
                                 canvas.width().into(),
@@ -171,7 +192,8 @@ pub fn Canvas(props: &Props) -> Html {
                         }
                     };
 
-                    let mut compute_limit: f64 = 100000_f64;
+                    let mut last_stats = None;
+                    let palette_cycle = settings.get_palette_cycle();
                     let render_callback: Rc<RefCell<Option<Closure<_>>>> =
                         Rc::new(RefCell::new(None));
 
@@ -179,23 +201,26 @@ pub fn Canvas(props: &Props) -> Html {
                         let engine = Rc::clone(engine);
                         let render_callback = Rc::clone(&render_callback);
                         let stop_render_loop = Rc::downgrade(&stop_render_loop);
+                        let on_stats = on_stats.clone();
                         Some(Closure::new(move || {
                             let perf = window().unwrap().performance().unwrap();
-                            let t0 = perf.now();
-                            let computed =
-                                engine.borrow_mut().compute(compute_limit.round() as usize);
-                            let dt = perf.now() - t0;
-                            if dt > 1000_f64 / 60_f64 {
-                                compute_limit /= 1.5;
-                            } else if dt < 1000_f64 / 100_f64
-                                && f64::from(u32::try_from(computed).unwrap()) >= compute_limit
-                            {
-                                compute_limit *= 1.5;
-                            }
+                            let computed = engine.borrow_mut().compute_for(FRAME_BUDGET, &perf);
 
+                            if let Some(speed) = palette_cycle {
+                                let phase = (perf.now() / 1000.0 * speed) as usize;
+                                engine.borrow_mut().set_palette_phase(phase);
+                            }
                             engine.borrow_mut().render();
                             draw_pixels();
 
+                            if computed > 0 || last_stats.is_none() {
+                                let stats = engine.borrow().stats();
+                                if last_stats != Some(stats) {
+                                    last_stats = Some(stats);
+                                    on_stats.emit(stats);
+                                }
+                            }
+
                             if let Some(stop) = stop_render_loop.upgrade() {
                                 if let Ok(mut stop) = stop.try_borrow_mut() {
                                     *stop = Some(
@@ -250,13 +275,14 @@ pub fn Canvas(props: &Props) -> Html {
             PtrEq::new(engine),
             props.settings.clone(),
             props.get_scroll_offset.clone(),
+            props.on_stats.clone(),
         ),
     );
 
     html! {
         <canvas
             ref={ canvas_ref }
-            class={ classes!("Canvas") }
+            class={ classes!("Canvas", "checkerboard") }
         />
     }
 }
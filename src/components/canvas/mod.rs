@@ -3,10 +3,12 @@ mod canvas_surface;
 
 use yew::function_component;
 use yew::html;
+use yew::Callback;
 use yew::Html;
 use yew::Properties;
 use yew::UseStateHandle;
 
+use crate::stats::RenderStats;
 use crate::EngineSettings;
 
 use canvas_controls::CanvasControls;
@@ -31,6 +33,7 @@ impl Pos {
 #[derive(PartialEq, Properties)]
 pub struct Props {
     pub settings: UseStateHandle<EngineSettings>,
+    pub on_stats: Callback<RenderStats>,
     #[prop_or(10_f64)]
     pub pan_trigger_threshold: f64,
 }
@@ -40,6 +43,7 @@ pub fn Canvas(props: &Props) -> Html {
     html! {
         <CanvasControls
             settings={ props.settings.clone() }
+            on_stats={ props.on_stats.clone() }
             pan_trigger_threshold={ props.pan_trigger_threshold }
         />
     }
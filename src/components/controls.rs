@@ -1,9 +1,13 @@
 use stylist::yew::styled_component;
+use wasm_bindgen::prelude::Closure;
 use wasm_bindgen::JsCast;
 use wasm_bindgen::JsValue;
 use web_sys::window;
 use web_sys::Event;
+use web_sys::File;
+use web_sys::FileReader;
 use web_sys::HtmlInputElement;
+use web_sys::HtmlSelectElement;
 use web_sys::InputEvent;
 use web_sys::SubmitEvent;
 use yew::classes;
@@ -15,12 +19,30 @@ use yew::Html;
 use yew::Properties;
 use yew::UseStateHandle;
 
+use crate::boundary::FillAlgorithm;
+use crate::buddhabrot::RenderMode;
+use crate::color_space::InterpolationSpace;
 use crate::components::collapse_box::CollapseBox;
 use crate::components::gradient_ribbon::GradientRibbon;
+use crate::components::lighting_controls::LightingControls;
+use crate::components::palette_cycle_controls::PaletteCycleControls;
 use crate::components::presets::Presets;
+use crate::easing::Easing;
+use crate::easing::Spline;
+use crate::gradient_file::GradientFormat;
+use crate::scheduler::Scheduler;
+use crate::utils::error_println;
+use crate::yew::console::error_log;
+use crate::yew::download::download;
 use crate::yew::state::UpdateUseStateHandle;
+use crate::Decomposition;
 use crate::EngineSettings;
+use crate::ExteriorColoring;
 use crate::GradientPivot;
+use crate::InteriorColoring;
+use crate::PivotScale;
+use crate::Spread;
+use crate::Transfer;
 
 fn on_submit(event: SubmitEvent) {
     event.prevent_default();
@@ -36,6 +58,45 @@ fn get_state_href(state_string: Option<&str>) -> Result<String, JsValue> {
     ))
 }
 
+/// Replace the pivots of the gradient with those in `file`, in the format its
+/// extension names.
+fn open_gradient_file(file: File, settings: UseStateHandle<EngineSettings>) {
+    let name = file.name();
+    let Some(format) = GradientFormat::from_file_name(&name) else {
+        error_println!("Unknown gradient format: {name}");
+        return;
+    };
+
+    let reader = match FileReader::new() {
+        Ok(reader) => reader,
+        Err(err) => {
+            error_log!("Failed to create file reader", err);
+            return;
+        }
+    };
+
+    let on_load = Closure::once_into_js({
+        let reader = reader.clone();
+        move || {
+            let text = match reader.result() {
+                Ok(result) => result.as_string().unwrap_or_default(),
+                Err(err) => {
+                    error_log!("Failed to read gradient file", err);
+                    return;
+                }
+            };
+            match format.read(&text) {
+                Ok(pivots) => settings.update(|s| s.gradient_set_relative_pivots(pivots.clone())),
+                Err(err) => error_println!("Failed to open gradient from {name}: {err}"),
+            }
+        }
+    });
+    reader.set_onload(Some(on_load.unchecked_ref()));
+    if let Err(err) = reader.read_as_text(&file) {
+        error_log!("Failed to read gradient file", err);
+    }
+}
+
 #[derive(PartialEq, Properties)]
 struct GradientProps {
     settings: UseStateHandle<EngineSettings>,
@@ -81,18 +142,331 @@ fn Gradient(props: &GradientProps) -> Html {
         props.settings.clone(),
     );
 
+    let on_set_interior_coloring = use_callback(
+        |interior: InteriorColoring, settings| {
+            settings.update(|s| s.gradient_set_interior_coloring(interior));
+        },
+        props.settings.clone(),
+    );
+
+    let on_set_decomposition = use_callback(
+        |decomposition: Decomposition, settings| {
+            settings.update(|s| s.gradient_set_decomposition(decomposition));
+        },
+        props.settings.clone(),
+    );
+
+    let set_pivot_easing = use_callback(
+        |(index, easing), settings| {
+            settings.update(|s| s.gradient_set_pivot_easing(index, easing));
+        },
+        props.settings.clone(),
+    );
+
+    let on_set_spline = use_callback(
+        |spline: Option<Spline>, settings| {
+            settings.update(|s| s.gradient_set_spline(spline));
+        },
+        props.settings.clone(),
+    );
+
+    let on_save_gradient = use_callback(
+        |format: GradientFormat, settings| {
+            let text = format.write(settings.get_gradient(), settings.get_iteration_limit());
+            let filename = format!("fraktal-gradient.{}", format.extension());
+            if let Err(err) = download(text.as_bytes(), format.mime_type(), &filename) {
+                error_println!("Failed to save {}: {:?}", format.name(), err);
+            }
+        },
+        props.settings.clone(),
+    );
+
+    let on_open_gradient = {
+        let settings = props.settings.clone();
+        move |e: Event| {
+            if let Some(el) = e
+                .target()
+                .and_then(|t| t.dyn_into::<HtmlInputElement>().ok())
+            {
+                if let Some(file) = el.files().and_then(|files| files.get(0)) {
+                    open_gradient_file(file, settings.clone());
+                }
+                // Opening the same file again should still replace the pivots
+                el.set_value("");
+            }
+        }
+    };
+
+    let save_buttons: Html = GradientFormat::ALL
+        .iter()
+        .map(|format| {
+            let on_save_gradient = on_save_gradient.clone();
+            let format = *format;
+            html! {
+                <button
+                    onclick={ move |_| on_save_gradient.emit(format) }
+                    title={ format!("Save as {}", format.name()) }
+                    type="button"
+                >
+                    { format!("Save .{}", format.extension()) }
+                </button>
+            }
+        })
+        .collect();
+    let accept = GradientFormat::ALL
+        .iter()
+        .map(|format| format!(".{}", format.extension()))
+        .collect::<Vec<_>>()
+        .join(",");
+
+    let spline = gradient.get_spline();
+    let spline_options: Html = Spline::ALL
+        .iter()
+        .enumerate()
+        .map(|(index, choice)| {
+            html! {
+                <option
+                    value={ index.to_string() }
+                    selected={ spline == Some(*choice) }
+                >
+                    { choice.name() }
+                </option>
+            }
+        })
+        .collect();
+
+    let on_set_interpolation = use_callback(
+        |space: InterpolationSpace, settings| {
+            settings.update(|s| s.gradient_set_interpolation(space));
+        },
+        props.settings.clone(),
+    );
+
+    let on_set_pivot_scale = use_callback(
+        |pivot_scale: PivotScale, settings| {
+            settings.update(|s| s.gradient_set_pivot_scale(pivot_scale));
+        },
+        props.settings.clone(),
+    );
+
+    let interpolation = gradient.get_interpolation();
+    let interpolation_options: Html = InterpolationSpace::ALL
+        .iter()
+        .enumerate()
+        .map(|(index, space)| {
+            html! {
+                <option value={ index.to_string() } selected={ *space == interpolation }>
+                    { format!("Interpolate in {}", space.name()) }
+                </option>
+            }
+        })
+        .collect();
+
+    let decomposition = gradient.get_decomposition();
+    let decomposition_key = match decomposition {
+        Decomposition::Off => "off",
+        Decomposition::Binary => "binary",
+        Decomposition::Nary { .. } => "nary",
+    };
+
+    let sectors_input: Html = if let Decomposition::Nary { sectors } = decomposition {
+        html! {
+            <input
+                class={ classes!("Decomposition-Sectors") }
+                min={ 2 }
+                onchange={
+                    let on_set_decomposition = on_set_decomposition.clone();
+                    move |e: Event| {
+                        if let Some(sectors) = e.target()
+                            .and_then(|t| t.dyn_into::<HtmlInputElement>().ok())
+                            .and_then(|el| el.value().parse().ok()) {
+                            on_set_decomposition.emit(Decomposition::Nary { sectors });
+                        }
+                    }
+                }
+                title="Number of sectors"
+                type="number"
+                value={ sectors.to_string() }
+            />
+        }
+    } else {
+        html! {}
+    };
+
+    let interior_coloring = gradient.get_interior_coloring();
+    let interior_options: Html = [
+        (InteriorColoring::Flat, "flat", "Single color"),
+        (InteriorColoring::Period, "period", "By period"),
+        (
+            InteriorColoring::PeriodMultiplier,
+            "period-multiplier",
+            "By period and multiplier",
+        ),
+    ]
+    .into_iter()
+    .map(|(interior, value, label)| {
+        html! {
+            <option { value } selected={ interior == interior_coloring }>
+                { label }
+            </option>
+        }
+    })
+    .collect();
+
+    let on_set_spread = use_callback(
+        |spread: Spread, settings| {
+            settings.update(|s| s.gradient_set_spread(spread));
+        },
+        props.settings.clone(),
+    );
+
+    let spread = gradient.get_spread();
+    let on_set_transfer = use_callback(
+        |transfer: Transfer, settings| {
+            settings.update(|s| s.set_transfer(transfer));
+        },
+        props.settings.clone(),
+    );
+
+    let transfer = *props.settings.get_transfer();
+    let transfer_key = match transfer {
+        Transfer::Identity => "identity",
+        Transfer::Log => "log",
+        Transfer::Sqrt => "sqrt",
+        Transfer::Power { .. } => "power",
+    };
+
+    let exponent_input: Html = if let Transfer::Power { exponent } = transfer {
+        html! {
+            <input
+                class={ classes!("Transfer-Exponent") }
+                min={ 0.1 }
+                onchange={
+                    let on_set_transfer = on_set_transfer.clone();
+                    move |e: Event| {
+                        if let Some(exponent) = e.target()
+                            .and_then(|t| t.dyn_into::<HtmlInputElement>().ok())
+                            .and_then(|el| el.value().parse().ok()) {
+                            on_set_transfer.emit(Transfer::Power { exponent });
+                        }
+                    }
+                }
+                step={ 0.1 }
+                title="Exponent"
+                type="number"
+                value={ exponent.to_string() }
+            />
+        }
+    } else {
+        html! {}
+    };
+
+    let spread_inputs: Html = match spread {
+        Spread::Pad => html! {},
+        Spread::Repeat { period, offset } | Spread::Mirror { period, offset } => {
+            let with = move |period, offset| match spread {
+                Spread::Mirror { .. } => Spread::Mirror { period, offset },
+                _ => Spread::Repeat { period, offset },
+            };
+            html! {
+                <span class={ classes!("Gradient-Spread") }>
+                    <input
+                        min={ 1 }
+                        onchange={
+                            let on_set_spread = on_set_spread.clone();
+                            move |e: Event| {
+                                if let Some(period) = e.target()
+                                    .and_then(|t| t.dyn_into::<HtmlInputElement>().ok())
+                                    .and_then(|el| el.value().parse().ok()) {
+                                    on_set_spread.emit(with(period, offset));
+                                }
+                            }
+                        }
+                        title="Period"
+                        type="number"
+                        value={ period.to_string() }
+                    />
+                    <input
+                        min={ 0 }
+                        onchange={
+                            let on_set_spread = on_set_spread.clone();
+                            move |e: Event| {
+                                if let Some(offset) = e.target()
+                                    .and_then(|t| t.dyn_into::<HtmlInputElement>().ok())
+                                    .and_then(|el| el.value().parse().ok()) {
+                                    on_set_spread.emit(with(period, offset));
+                                }
+                            }
+                        }
+                        title="Offset"
+                        type="number"
+                        value={ offset.to_string() }
+                    />
+                </span>
+            }
+        }
+    };
+
+    let pivot_scale = gradient.get_pivot_scale();
+    let (pivot_max, pivot_step) = if pivot_scale == PivotScale::Absolute {
+        ((num_colors - 1) as f64, "1")
+    } else {
+        (1.0, "0.001")
+    };
+
+    let easing_select = |index: usize, easing: Easing| -> Html {
+        if index == 0 || spline.is_some() {
+            return html! {};
+        }
+        let options: Html = Easing::ALL
+            .iter()
+            .enumerate()
+            .map(|(option, choice)| {
+                html! {
+                    <option value={ option.to_string() } selected={ *choice == easing }>
+                        { choice.name() }
+                    </option>
+                }
+            })
+            .collect();
+        html! {
+            <select
+                class={ classes!("grid-first-column", "Gradient-Easing") }
+                onchange={
+                    let set_pivot_easing = set_pivot_easing.clone();
+                    move |e: Event| {
+                        if let Some(easing) = e.target()
+                            .and_then(|t| t.dyn_into::<HtmlSelectElement>().ok())
+                            .and_then(|el| el.value().parse::<usize>().ok())
+                            .and_then(|option| Easing::ALL.get(option)) {
+                            set_pivot_easing.emit((index, *easing));
+                        }
+                    }
+                }
+                title="Easing"
+            >
+                { options }
+            </select>
+        }
+    };
+
+    let inside_hex = gradient.get_inside_color().as_hex();
+    let inside_alpha = gradient.get_inside_color().get_alpha();
+
     let pivots: Html = gradient
         .get_pivots()
         .iter()
         .enumerate()
         .map(|(index, pivot): (usize, &GradientPivot)| {
             let color_hex = pivot.color.as_hex();
+            let alpha = pivot.color.get_alpha();
 
             html! {
                 < key={ index }>
+                    { easing_select(index, pivot.easing) }
                     <input
                         class={ classes!("grid-first-column")}
-                        max={ ( num_colors - 1 ).to_string() }
+                        max={ pivot_max.to_string() }
                         min={ 0 }
                         oninput={
                             let set_pivot_value = set_pivot_value.clone();
@@ -104,22 +478,44 @@ fn Gradient(props: &GradientProps) -> Html {
                                 }
                             }
                         }
+                        step={ pivot_step }
                         type="range"
                         value={ pivot.value.to_string() }
                     />
-                    <input
-                        oninput={
-                            let set_pivot_color = set_pivot_color.clone();
-                            move |e: InputEvent| {
-                                if let Some(el) = e.target()
-                                    .and_then(|t| t.dyn_into::<HtmlInputElement>().ok()) {
-                                    set_pivot_color.emit((index, el.value()));
+                    <span class={ classes!("Gradient-Color") }>
+                        <input
+                            oninput={
+                                let set_pivot_color = set_pivot_color.clone();
+                                move |e: InputEvent| {
+                                    if let Some(el) = e.target()
+                                        .and_then(|t| t.dyn_into::<HtmlInputElement>().ok()) {
+                                        set_pivot_color
+                                            .emit((index, format!("{}{alpha:02x}", el.value())));
+                                    }
                                 }
                             }
-                        }
-                        type="color"
-                        value={ color_hex }
-                    />
+                            type="color"
+                            value={ color_hex.clone() }
+                        />
+                        <input
+                            max="255"
+                            min="0"
+                            oninput={
+                                let set_pivot_color = set_pivot_color.clone();
+                                move |e: InputEvent| {
+                                    if let Some(alpha) = e.target()
+                                        .and_then(|t| t.dyn_into::<HtmlInputElement>().ok())
+                                        .and_then(|el| el.value().parse::<u8>().ok()) {
+                                        set_pivot_color
+                                            .emit((index, format!("{color_hex}{alpha:02x}")));
+                                    }
+                                }
+                            }
+                            title="Opacity"
+                            type="range"
+                            value={ alpha.to_string() }
+                        />
+                    </span>
                     <button
                         onclick={
                             let add_gradient_pivot = add_gradient_pivot.clone();
@@ -149,24 +545,418 @@ fn Gradient(props: &GradientProps) -> Html {
                 classes={ vec!["grid-first-column"] }
                 gradient={ gradient }
                 { num_colors }
+                { transfer }
             />
 
             { pivots }
 
-            <span class={ classes!("grid-first-column") }>{ "Color inside set: " }</span>
-            <input
+            <select
+                class={ classes!("grid-first-column") }
+                onchange={
+                    let on_set_interpolation = on_set_interpolation.clone();
+                    move |e: Event| {
+                        if let Some(space) = e.target()
+                            .and_then(|t| t.dyn_into::<HtmlSelectElement>().ok())
+                            .and_then(|el| el.value().parse::<usize>().ok())
+                            .and_then(|index| InterpolationSpace::ALL.get(index)) {
+                            on_set_interpolation.emit(*space);
+                        }
+                    }
+                }
+            >
+                { interpolation_options }
+            </select>
+
+            <select
+                class={ classes!("grid-first-column") }
                 onchange={
-                    let on_set_inside_color = on_set_inside_color.clone();
+                    let on_set_spline = on_set_spline.clone();
                     move |e: Event| {
                         if let Some(el) = e.target()
-                            .and_then(|t| t.dyn_into::<HtmlInputElement>().ok()) {
-                                on_set_inside_color.emit(el.value());
-                            }
+                            .and_then(|t| t.dyn_into::<HtmlSelectElement>().ok()) {
+                            on_set_spline.emit(
+                                el.value()
+                                    .parse::<usize>()
+                                    .ok()
+                                    .and_then(|index| Spline::ALL.get(index).copied()),
+                            );
+                        }
                     }
                 }
-                type="color"
-                value={ gradient.get_inside_color().as_hex() }
-            />
+            >
+                <option value="segments" selected={ spline.is_none() }>
+                    { "Eased segments" }
+                </option>
+                { spline_options }
+            </select>
+
+            <select
+                class={ classes!("grid-first-column") }
+                onchange={
+                    let on_set_pivot_scale = on_set_pivot_scale.clone();
+                    move |e: Event| {
+                        if let Some(el) = e.target()
+                            .and_then(|t| t.dyn_into::<HtmlSelectElement>().ok()) {
+                            on_set_pivot_scale.emit(match el.value().as_str() {
+                                "iteration-limit" => PivotScale::IterationLimit,
+                                "escape-range" => PivotScale::EscapeRange,
+                                _ => PivotScale::Absolute,
+                            });
+                        }
+                    }
+                }
+            >
+                <option value="absolute" selected={ pivot_scale == PivotScale::Absolute }>
+                    { "Pivots at escape counts" }
+                </option>
+                <option
+                    value="iteration-limit"
+                    selected={ pivot_scale == PivotScale::IterationLimit }
+                >
+                    { "Pivots relative to level of detail" }
+                </option>
+                <option value="escape-range" selected={ pivot_scale == PivotScale::EscapeRange }>
+                    { "Pivots relative to escape range" }
+                </option>
+            </select>
+
+            <select
+                class={ classes!("grid-first-column") }
+                onchange={
+                    let on_set_spread = on_set_spread.clone();
+                    let period = gradient.last_escape_count(num_colors).max(1);
+                    move |e: Event| {
+                        if let Some(el) = e.target()
+                            .and_then(|t| t.dyn_into::<HtmlSelectElement>().ok()) {
+                            on_set_spread.emit(match el.value().as_str() {
+                                "repeat" => Spread::Repeat { period, offset: 0 },
+                                "mirror" => Spread::Mirror { period, offset: 0 },
+                                _ => Spread::Pad,
+                            });
+                        }
+                    }
+                }
+            >
+                <option value="pad" selected={ spread == Spread::Pad }>
+                    { "Pad past last pivot" }
+                </option>
+                <option value="repeat" selected={ matches!(spread, Spread::Repeat { .. }) }>
+                    { "Repeat" }
+                </option>
+                <option value="mirror" selected={ matches!(spread, Spread::Mirror { .. }) }>
+                    { "Mirror" }
+                </option>
+            </select>
+            { spread_inputs }
+
+            <select
+                class={ classes!("grid-first-column") }
+                onchange={
+                    let on_set_transfer = on_set_transfer.clone();
+                    move |e: Event| {
+                        if let Some(el) = e.target()
+                            .and_then(|t| t.dyn_into::<HtmlSelectElement>().ok()) {
+                            on_set_transfer.emit(match el.value().as_str() {
+                                "log" => Transfer::Log,
+                                "sqrt" => Transfer::Sqrt,
+                                "power" => Transfer::Power { exponent: 0.5 },
+                                _ => Transfer::Identity,
+                            });
+                        }
+                    }
+                }
+            >
+                <option value="identity" selected={ transfer_key == "identity" }>
+                    { "Escape counts as they are" }
+                </option>
+                <option value="log" selected={ transfer_key == "log" }>
+                    { "Logarithm of escape counts" }
+                </option>
+                <option value="sqrt" selected={ transfer_key == "sqrt" }>
+                    { "Square root of escape counts" }
+                </option>
+                <option value="power" selected={ transfer_key == "power" }>
+                    { "Power of escape counts" }
+                </option>
+            </select>
+            { exponent_input }
+
+            <select
+                class={ classes!("grid-first-column") }
+                onchange={
+                    let on_set_decomposition = on_set_decomposition.clone();
+                    move |e: Event| {
+                        if let Some(el) = e.target()
+                            .and_then(|t| t.dyn_into::<HtmlSelectElement>().ok()) {
+                            on_set_decomposition.emit(match el.value().as_str() {
+                                "binary" => Decomposition::Binary,
+                                "nary" => Decomposition::Nary { sectors: 8 },
+                                _ => Decomposition::Off,
+                            });
+                        }
+                    }
+                }
+            >
+                <option value="off" selected={ decomposition_key == "off" }>
+                    { "No decomposition" }
+                </option>
+                <option value="binary" selected={ decomposition_key == "binary" }>
+                    { "Binary decomposition" }
+                </option>
+                <option value="nary" selected={ decomposition_key == "nary" }>
+                    { "Field lines" }
+                </option>
+            </select>
+            { sectors_input }
+
+            <select
+                class={ classes!("grid-first-column") }
+                onchange={
+                    let on_set_interior_coloring = on_set_interior_coloring.clone();
+                    move |e: Event| {
+                        if let Some(el) = e.target()
+                            .and_then(|t| t.dyn_into::<HtmlSelectElement>().ok()) {
+                            on_set_interior_coloring.emit(match el.value().as_str() {
+                                "period" => InteriorColoring::Period,
+                                "period-multiplier" => InteriorColoring::PeriodMultiplier,
+                                _ => InteriorColoring::Flat,
+                            });
+                        }
+                    }
+                }
+            >
+                { interior_options }
+            </select>
+            <span class={ classes!("grid-first-column") }>
+                {
+                    if interior_coloring == InteriorColoring::Flat {
+                        "Color inside set: "
+                    } else {
+                        "Color of unresolved cycles: "
+                    }
+                }
+            </span>
+            <span class={ classes!("Gradient-Color") }>
+                <input
+                    onchange={
+                        let on_set_inside_color = on_set_inside_color.clone();
+                        move |e: Event| {
+                            if let Some(el) = e.target()
+                                .and_then(|t| t.dyn_into::<HtmlInputElement>().ok()) {
+                                    on_set_inside_color
+                                        .emit(format!("{}{inside_alpha:02x}", el.value()));
+                                }
+                        }
+                    }
+                    type="color"
+                    value={ inside_hex.clone() }
+                />
+                <input
+                    max="255"
+                    min="0"
+                    oninput={
+                        let on_set_inside_color = on_set_inside_color.clone();
+                        move |e: InputEvent| {
+                            if let Some(alpha) = e.target()
+                                .and_then(|t| t.dyn_into::<HtmlInputElement>().ok())
+                                .and_then(|el| el.value().parse::<u8>().ok()) {
+                                    on_set_inside_color
+                                        .emit(format!("{inside_hex}{alpha:02x}"));
+                                }
+                        }
+                    }
+                    title="Opacity"
+                    type="range"
+                    value={ inside_alpha.to_string() }
+                />
+            </span>
+
+            <span class={ classes!("grid-first-column", "Gradient-Files") }>
+                { save_buttons }
+                <label>
+                    { "Open gradient file: " }
+                    <input { accept } onchange={ on_open_gradient } type="file" />
+                </label>
+            </span>
+        </div>
+    }
+}
+
+#[derive(PartialEq, Properties)]
+struct RenderModeProps {
+    settings: UseStateHandle<EngineSettings>,
+}
+
+#[styled_component]
+fn RenderModeSelect(props: &RenderModeProps) -> Html {
+    let render_mode = *props.settings.get_render_mode();
+
+    let on_set_render_mode = use_callback(
+        |render_mode: RenderMode, settings| {
+            settings.update(|s| s.set_render_mode(render_mode));
+        },
+        props.settings.clone(),
+    );
+
+    let mode_key = match render_mode {
+        RenderMode::EscapeTime => "escape-time",
+        RenderMode::Buddhabrot => "buddhabrot",
+        RenderMode::Nebulabrot { .. } => "nebulabrot",
+    };
+
+    let band_inputs: Html = if let RenderMode::Nebulabrot { red, green, blue } = render_mode {
+        [("R", red), ("G", green), ("B", blue)]
+            .into_iter()
+            .enumerate()
+            .map(|(channel, (label, limit))| {
+                html! {
+                    <label key={ channel }>
+                        { label }
+                        <input
+                            min={ 1 }
+                            onchange={
+                                let on_set_render_mode = on_set_render_mode.clone();
+                                move |e: Event| {
+                                    if let Some(value) = e.target()
+                                        .and_then(|t| t.dyn_into::<HtmlInputElement>().ok())
+                                        .and_then(|el| el.value().parse().ok()) {
+                                        let mut limits = [red, green, blue];
+                                        limits[channel] = value;
+                                        let [red, green, blue] = limits;
+                                        on_set_render_mode.emit(RenderMode::Nebulabrot { red, green, blue });
+                                    }
+                                }
+                            }
+                            type="number"
+                            value={ limit.to_string() }
+                        />
+                    </label>
+                }
+            })
+            .collect()
+    } else {
+        html! {}
+    };
+
+    html! {
+        <div class={ classes!("Render-Mode") }>
+            <div class={ classes!("flex-row", "flex-align-center") }>
+                <span class={ classes!("flex-stretch") }>{ "Rendering mode:" }</span>
+                <select
+                    onchange={
+                        let on_set_render_mode = on_set_render_mode.clone();
+                        move |e: Event| {
+                            if let Some(el) = e.target()
+                                .and_then(|t| t.dyn_into::<HtmlSelectElement>().ok()) {
+                                on_set_render_mode.emit(match el.value().as_str() {
+                                    "buddhabrot" => RenderMode::Buddhabrot,
+                                    "nebulabrot" => RenderMode::nebulabrot_default(),
+                                    _ => RenderMode::EscapeTime,
+                                });
+                            }
+                        }
+                    }
+                >
+                    <option value="escape-time" selected={ mode_key == "escape-time" }>
+                        { "Escape time" }
+                    </option>
+                    <option value="buddhabrot" selected={ mode_key == "buddhabrot" }>
+                        { "Buddhabrot" }
+                    </option>
+                    <option value="nebulabrot" selected={ mode_key == "nebulabrot" }>
+                        { "Nebulabrot" }
+                    </option>
+                </select>
+            </div>
+            <div class={ classes!("flex-row", "flex-align-center") }>
+                { band_inputs }
+            </div>
+        </div>
+    }
+}
+
+#[derive(PartialEq, Properties)]
+struct ExteriorColoringProps {
+    settings: UseStateHandle<EngineSettings>,
+}
+
+#[styled_component]
+fn ExteriorColoringSelect(props: &ExteriorColoringProps) -> Html {
+    let exterior_coloring = *props.settings.get_exterior_coloring();
+
+    let on_set_exterior_coloring = use_callback(
+        |exterior_coloring: ExteriorColoring, settings| {
+            settings.update(|s| s.set_exterior_coloring(exterior_coloring));
+        },
+        props.settings.clone(),
+    );
+
+    let coloring_key = match exterior_coloring {
+        ExteriorColoring::EscapeTime => "escape-time",
+        ExteriorColoring::StripeAverage { .. } => "stripe",
+        ExteriorColoring::CurvatureAverage => "curvature",
+    };
+
+    let density_input: Html = if let ExteriorColoring::StripeAverage { density } = exterior_coloring
+    {
+        html! {
+            <label>
+                { "Stripes" }
+                <input
+                    min={ 1 }
+                    onchange={
+                        let on_set_exterior_coloring = on_set_exterior_coloring.clone();
+                        move |e: Event| {
+                            if let Some(density) = e.target()
+                                .and_then(|t| t.dyn_into::<HtmlInputElement>().ok())
+                                .and_then(|el| el.value().parse().ok()) {
+                                on_set_exterior_coloring.emit(ExteriorColoring::StripeAverage { density });
+                            }
+                        }
+                    }
+                    step={ 1 }
+                    type="number"
+                    value={ density.to_string() }
+                />
+            </label>
+        }
+    } else {
+        html! {}
+    };
+
+    html! {
+        <div class={ classes!("Render-Mode") }>
+            <div class={ classes!("flex-row", "flex-align-center") }>
+                <span class={ classes!("flex-stretch") }>{ "Color outside set by:" }</span>
+                <select
+                    onchange={
+                        let on_set_exterior_coloring = on_set_exterior_coloring.clone();
+                        move |e: Event| {
+                            if let Some(el) = e.target()
+                                .and_then(|t| t.dyn_into::<HtmlSelectElement>().ok()) {
+                                on_set_exterior_coloring.emit(match el.value().as_str() {
+                                    "stripe" => ExteriorColoring::StripeAverage { density: 5.0 },
+                                    "curvature" => ExteriorColoring::CurvatureAverage,
+                                    _ => ExteriorColoring::EscapeTime,
+                                });
+                            }
+                        }
+                    }
+                >
+                    <option value="escape-time" selected={ coloring_key == "escape-time" }>
+                        { "Escape time" }
+                    </option>
+                    <option value="stripe" selected={ coloring_key == "stripe" }>
+                        { "Stripe average" }
+                    </option>
+                    <option value="curvature" selected={ coloring_key == "curvature" }>
+                        { "Curvature average" }
+                    </option>
+                </select>
+            </div>
+            <div class={ classes!("flex-row", "flex-align-center") }>
+                { density_input }
+            </div>
         </div>
     }
 }
@@ -212,6 +1002,76 @@ pub fn Controls(props: &Props) -> Html {
         (max_precision.clone(), num_colors, on_set_num_colors.clone()),
     );
 
+    let uniform_fill = props.settings.get_uniform_fill_guard().is_some();
+    let on_toggle_uniform_fill = use_callback(
+        |e: Event, settings| {
+            if let Some(el) = e
+                .target()
+                .and_then(|t| t.dyn_into::<HtmlInputElement>().ok())
+            {
+                settings.update(|s| {
+                    s.set_uniform_fill_guard(
+                        Some(EngineSettings::DEFAULT_UNIFORM_FILL_GUARD).filter(|_| el.checked()),
+                    )
+                });
+            }
+        },
+        props.settings.clone(),
+    );
+
+    let fill_algorithm = *props.settings.get_fill_algorithm();
+    let on_set_fill_algorithm = use_callback(
+        |e: Event, settings| {
+            if let Some(el) = e
+                .target()
+                .and_then(|t| t.dyn_into::<HtmlSelectElement>().ok())
+            {
+                let fill_algorithm = match el.value().as_str() {
+                    "boundary-trace" => FillAlgorithm::BoundaryTrace,
+                    _ => FillAlgorithm::Trisect,
+                };
+                settings.update(|s| s.set_fill_algorithm(fill_algorithm));
+            }
+        },
+        props.settings.clone(),
+    );
+
+    let scheduler = *props.settings.get_scheduler();
+    let on_set_scheduler = use_callback(
+        |e: Event, settings| {
+            if let Some(el) = e
+                .target()
+                .and_then(|t| t.dyn_into::<HtmlSelectElement>().ok())
+            {
+                let scheduler = match el.value().as_str() {
+                    "spiral" => Scheduler::Spiral,
+                    "scanline" => Scheduler::Scanline,
+                    "random" => Scheduler::Random,
+                    "cursor-follow" => Scheduler::CursorFollow,
+                    _ => Scheduler::FocusFirst,
+                };
+                settings.update(|s| s.set_scheduler(scheduler));
+            }
+        },
+        props.settings.clone(),
+    );
+    let scheduler_options: Html = [
+        (Scheduler::FocusFirst, "focus-first", "Focus first"),
+        (Scheduler::Spiral, "spiral", "Spiral"),
+        (Scheduler::Scanline, "scanline", "Scanline"),
+        (Scheduler::Random, "random", "Random"),
+        (Scheduler::CursorFollow, "cursor-follow", "Follow cursor"),
+    ]
+    .into_iter()
+    .map(|(option, value, label)| {
+        html! {
+            <option { value } selected={ option == scheduler }>
+                { label }
+            </option>
+        }
+    })
+    .collect();
+
     let on_zoom_in = use_callback(
         |_, settings| {
             settings.update(|s| s.zoom_in(2_f64));
@@ -271,9 +1131,51 @@ pub fn Controls(props: &Props) -> Html {
                     </span>
                 </div>
 
+                <RenderModeSelect settings={ props.settings.clone() }/>
+
+                <label class={ classes!("Uniform-Fill") }>
+                    <input
+                        checked={ uniform_fill }
+                        onchange={ on_toggle_uniform_fill }
+                        type="checkbox"
+                    />
+                    { "Fast fill (may miss fine details)" }
+                </label>
+
+                <div class={ classes!("Render-Mode", "flex-row", "flex-align-center") }>
+                    <span class={ classes!("flex-stretch") }>{ "Fill:" }</span>
+                    <select onchange={ on_set_fill_algorithm }>
+                        <option
+                            value="trisect"
+                            selected={ fill_algorithm == FillAlgorithm::Trisect }
+                        >
+                            { "Trisect" }
+                        </option>
+                        <option
+                            value="boundary-trace"
+                            selected={ fill_algorithm == FillAlgorithm::BoundaryTrace }
+                        >
+                            { "Boundary trace" }
+                        </option>
+                    </select>
+                </div>
+
+                <div class={ classes!("Render-Mode", "flex-row", "flex-align-center") }>
+                    <span class={ classes!("flex-stretch") }>{ "Compute order:" }</span>
+                    <select onchange={ on_set_scheduler }>
+                        { scheduler_options }
+                    </select>
+                </div>
+
                 <p class={ css!{ margin-bottom: ${"0.2em"}; }}>{ "Color map:" }</p>
                 <Gradient settings={ props.settings.clone() }/>
 
+                <ExteriorColoringSelect settings={ props.settings.clone() }/>
+
+                <LightingControls settings={ props.settings.clone() }/>
+
+                <PaletteCycleControls settings={ props.settings.clone() }/>
+
                 <CollapseBox title="Presets">
                     <Presets current={ state_string.map(AttrValue::from) } />
                 </CollapseBox>
@@ -1,45 +1,86 @@
 use std::rc::Rc;
 
-use stylist::yew::styled_component;
+use wasm_bindgen::Clamped;
+use wasm_bindgen::JsCast;
+use wasm_bindgen::JsValue;
+use web_sys::CanvasRenderingContext2d;
+use web_sys::HtmlCanvasElement;
+use web_sys::ImageData;
 use yew::classes;
+use yew::function_component;
 use yew::html;
+use yew::use_effect_with_deps;
+use yew::use_node_ref;
 use yew::Html;
+use yew::NodeRef;
 use yew::Properties;
 
+use crate::utils::error_println;
+use crate::Transfer;
+
+/// Most pixels across the ribbon. Up to this many colors, each escape count
+/// gets a pixel of its own.
+const MAX_WIDTH: usize = 1024;
+
 #[derive(PartialEq, Properties)]
 pub struct Props<'a> {
     pub classes: Option<Vec<&'a str>>,
     pub num_colors: usize,
     pub gradient: Rc<crate::Gradient>,
+    #[prop_or_default]
+    pub transfer: Transfer,
 }
 
-#[styled_component]
+/// Draw the colors that escape counts get, left to right, as a single row of
+/// pixels that CSS stretches to fit.
+fn draw(
+    canvas_ref: &NodeRef,
+    gradient: &crate::Gradient,
+    num_colors: usize,
+    transfer: &Transfer,
+) -> Result<(), JsValue> {
+    if let Some(canvas) = canvas_ref.cast::<HtmlCanvasElement>() {
+        let width = num_colors.clamp(1, MAX_WIDTH);
+        let pixels: Vec<u8> = gradient
+            .ribbon_colors(num_colors, transfer, width)
+            .iter()
+            .flat_map(|color| color.as_rgba())
+            .collect();
+
+        canvas.set_width(width as u32);
+        canvas.set_height(1);
+        let ctx: CanvasRenderingContext2d = canvas
+            .get_context("2d")?
+            .ok_or("No 2D context")?
+            .dyn_into()?;
+        let image_data = ImageData::new_with_u8_clamped_array(Clamped(&pixels), width as u32)?;
+        ctx.put_image_data(&image_data, 0.0, 0.0)?;
+    }
+    Ok(())
+}
+
+#[function_component]
 pub fn GradientRibbon(props: &Props<'static>) -> Html {
-    let ribbon_gradient: Vec<String> = props
-        .gradient
-        .get_pivots()
-        .iter()
-        .map(|pivot| {
-            format!(
-                "{} {}%",
-                pivot.color.as_hex(),
-                100_f64 * pivot.value as f64 / props.num_colors as f64
-            )
-        })
-        .collect();
+    let canvas_ref = use_node_ref();
+
+    use_effect_with_deps(
+        |(canvas_ref, gradient, num_colors, transfer)| {
+            if let Err(err) = draw(canvas_ref, gradient, *num_colors, transfer) {
+                error_println!("Failed to draw gradient ribbon: {:?}", err);
+            }
+        },
+        (
+            canvas_ref.clone(),
+            Rc::clone(&props.gradient),
+            props.num_colors,
+            props.transfer,
+        ),
+    );
 
     html! {
-        <div
-            class={ classes!(
-                &props.classes,
-                "Gradient-Ribbon",
-                css!{
-                    background: linear-gradient(
-                        to right,
-                        ${ribbon_gradient.join(", ")}
-                    );
-                },
-            )}
+        <canvas
+            ref={ canvas_ref }
+            class={ classes!(&props.classes, "Gradient-Ribbon", "checkerboard") }
         />
     }
 }
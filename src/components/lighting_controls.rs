@@ -0,0 +1,134 @@
+use stylist::yew::styled_component;
+use wasm_bindgen::JsCast;
+use web_sys::Event;
+use web_sys::HtmlInputElement;
+use web_sys::InputEvent;
+use yew::classes;
+use yew::function_component;
+use yew::html;
+use yew::use_callback;
+use yew::Callback;
+use yew::Html;
+use yew::Properties;
+use yew::UseStateHandle;
+
+use crate::lighting::Lighting;
+use crate::yew::state::UpdateUseStateHandle;
+use crate::EngineSettings;
+
+#[derive(PartialEq, Properties)]
+pub struct SliderProps {
+    pub label: &'static str,
+    pub max: f64,
+    pub min: f64,
+    pub on_input: Callback<f64>,
+    pub step: f64,
+    pub value: f64,
+}
+
+#[function_component]
+pub fn Slider(props: &SliderProps) -> Html {
+    html! {
+        <>
+            <span class={ classes!("grid-first-column") }>{ props.label }</span>
+            <input
+                max={ props.max.to_string() }
+                min={ props.min.to_string() }
+                oninput={
+                    let on_input = props.on_input.clone();
+                    move |e: InputEvent| {
+                        if let Some(value) = e.target()
+                            .and_then(|t| t.dyn_into::<HtmlInputElement>().ok())
+                            .and_then(|el| el.value().parse().ok()) {
+                            on_input.emit(value);
+                        }
+                    }
+                }
+                step={ props.step.to_string() }
+                type="range"
+                value={ props.value.to_string() }
+            />
+        </>
+    }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct Props {
+    pub settings: UseStateHandle<EngineSettings>,
+}
+
+#[styled_component]
+pub fn LightingControls(props: &Props) -> Html {
+    let lighting = props.settings.get_lighting().copied();
+
+    let on_set_lighting = use_callback(
+        |lighting: Option<Lighting>, settings| {
+            settings.update(|s| s.set_lighting(lighting));
+        },
+        props.settings.clone(),
+    );
+
+    let on_toggle = {
+        let on_set_lighting = on_set_lighting.clone();
+        move |e: Event| {
+            if let Some(el) = e
+                .target()
+                .and_then(|t| t.dyn_into::<HtmlInputElement>().ok())
+            {
+                on_set_lighting.emit(Some(Lighting::default()).filter(|_| el.checked()));
+            }
+        }
+    };
+
+    let sliders: Html = if let Some(lighting) = lighting {
+        let update = |f: fn(Lighting, f64) -> Lighting| {
+            let on_set_lighting = on_set_lighting.clone();
+            Callback::from(move |value: f64| on_set_lighting.emit(Some(f(lighting, value))))
+        };
+
+        html! {
+            <>
+                <Slider
+                    label="Angle"
+                    max={ 360.0 }
+                    min={ 0.0 }
+                    on_input={ update(|l, angle| Lighting { angle, ..l }) }
+                    step={ 1.0 }
+                    value={ lighting.angle }
+                />
+                <Slider
+                    label="Elevation"
+                    max={ 90.0 }
+                    min={ 0.0 }
+                    on_input={ update(|l, elevation| Lighting { elevation, ..l }) }
+                    step={ 1.0 }
+                    value={ lighting.elevation }
+                />
+                <Slider
+                    label="Strength"
+                    max={ 1.0 }
+                    min={ 0.0 }
+                    on_input={ update(|l, strength| Lighting { strength, ..l }) }
+                    step={ 0.01 }
+                    value={ lighting.strength }
+                />
+            </>
+        }
+    } else {
+        html! {}
+    };
+
+    html! {
+        <div class={ classes!("Lighting-Controls") }>
+            <label class={ classes!("grid-first-column") }>
+                <input
+                    checked={ lighting.is_some() }
+                    onchange={ on_toggle }
+                    type="checkbox"
+                />
+                { "3D lighting" }
+            </label>
+            { sliders }
+        </div>
+    }
+}
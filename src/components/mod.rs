@@ -4,5 +4,10 @@ pub mod collapse_box;
 pub mod controls;
 pub mod github_corner;
 pub mod gradient_ribbon;
+pub mod lighting_controls;
+pub mod palette_cycle_controls;
 pub mod presets;
+pub mod render_progress;
 pub mod sidebar;
+
+pub use crate::yew::console::log_to_console;
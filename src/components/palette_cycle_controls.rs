@@ -0,0 +1,133 @@
+use std::time::Duration;
+
+use stylist::yew::styled_component;
+use wasm_bindgen::JsCast;
+use wasm_bindgen::JsValue;
+use web_sys::Event;
+use web_sys::HtmlInputElement;
+use yew::classes;
+use yew::html;
+use yew::use_callback;
+use yew::Html;
+use yew::Properties;
+use yew::UseStateHandle;
+
+use crate::components::lighting_controls::Slider;
+use crate::export::write_palette_cycle;
+use crate::utils::error_println;
+use crate::yew::download::download;
+use crate::yew::state::UpdateUseStateHandle;
+use crate::Engine;
+use crate::EngineSettings;
+
+/// Speed of a newly started cycle, in steps along the gradient per second.
+const DEFAULT_SPEED: f64 = 20.0;
+
+/// Frame rate of an exported cycle.
+const EXPORT_FPS: f64 = 25.0;
+
+/// Most frames in an exported cycle, to keep the file size reasonable.
+const MAX_EXPORT_FRAMES: usize = 250;
+
+/// Render the current view from scratch and save one full cycle of its
+/// colors at `speed` as an animated PNG.
+fn export_cycle(settings: &EngineSettings, speed: f64) -> Result<(), JsValue> {
+    let mut settings = settings.clone();
+    let mut engine = Engine::new(&settings);
+    engine.apply_settings(&mut settings);
+    engine.compute(usize::MAX);
+
+    let cycle_length = engine.palette_cycle_length();
+    let cycle_seconds = cycle_length as f64 / speed;
+    let frames = ((cycle_seconds * EXPORT_FPS).round() as usize)
+        .clamp(1, std::cmp::min(cycle_length, MAX_EXPORT_FRAMES));
+    let frame_delay = Duration::from_secs_f64(cycle_seconds / frames as f64);
+
+    let mut encoded = Vec::new();
+    write_palette_cycle(
+        &mut encoded,
+        &mut engine,
+        frames,
+        frame_delay,
+        Some(&settings),
+    )
+    .map_err(|err| JsValue::from(err.to_string()))?;
+    download(&encoded, "image/png", "fraktal-cycle.png")
+}
+
+#[derive(PartialEq, Properties)]
+pub struct Props {
+    pub settings: UseStateHandle<EngineSettings>,
+}
+
+#[styled_component]
+pub fn PaletteCycleControls(props: &Props) -> Html {
+    let speed = props.settings.get_palette_cycle();
+
+    let on_set_speed = use_callback(
+        |speed: Option<f64>, settings| {
+            settings.update(|s| s.set_palette_cycle(speed));
+        },
+        props.settings.clone(),
+    );
+
+    let on_toggle = {
+        let on_set_speed = on_set_speed.clone();
+        move |e: Event| {
+            if let Some(el) = e
+                .target()
+                .and_then(|t| t.dyn_into::<HtmlInputElement>().ok())
+            {
+                on_set_speed.emit(Some(DEFAULT_SPEED).filter(|_| el.checked()));
+            }
+        }
+    };
+
+    let on_export = use_callback(
+        |_, settings| {
+            let speed = settings.get_palette_cycle().unwrap_or(DEFAULT_SPEED);
+            if let Err(err) = export_cycle(settings, speed) {
+                error_println!("Failed to export color cycle: {:?}", err);
+            }
+        },
+        props.settings.clone(),
+    );
+
+    let speed_controls: Html = if let Some(speed) = speed {
+        html! {
+            <>
+                <Slider
+                    label="Speed"
+                    max={ 500.0 }
+                    min={ 1.0 }
+                    on_input={ on_set_speed.reform(Some) }
+                    step={ 1.0 }
+                    value={ speed }
+                />
+                <button
+                    class={ classes!("grid-first-column") }
+                    onclick={ on_export }
+                    type="button"
+                >
+                    { "Save cycle as animated PNG" }
+                </button>
+            </>
+        }
+    } else {
+        html! {}
+    };
+
+    html! {
+        <div class={ classes!("Palette-Cycle-Controls") }>
+            <label class={ classes!("grid-first-column") }>
+                <input
+                    checked={ speed.is_some() }
+                    onchange={ on_toggle }
+                    type="checkbox"
+                />
+                { "Cycle colors" }
+            </label>
+            { speed_controls }
+        </div>
+    }
+}
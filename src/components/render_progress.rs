@@ -0,0 +1,40 @@
+use yew::classes;
+use yew::function_component;
+use yew::html;
+use yew::Html;
+use yew::Properties;
+
+use crate::stats::format_throughput;
+use crate::stats::RenderStats;
+
+#[derive(PartialEq, Properties)]
+pub struct Props {
+    pub stats: RenderStats,
+}
+
+#[function_component]
+pub fn RenderProgress(props: &Props) -> Html {
+    let stats = &props.stats;
+    let progress = stats.progress();
+
+    html! {
+        <div class={ classes!("Render-Progress") }>
+            <progress
+                max="1"
+                title={ format!("{} of {} pixels", stats.finalized_pixels(), stats.total_pixels) }
+                value={ progress.to_string() }
+            />
+            <div class={ classes!("flex-row") }>
+                <span class={ classes!("flex-stretch") }>
+                    { format!("{:.0}%", progress * 100.0) }
+                    if stats.is_done() {
+                        { format!(", {:.0}% inside", stats.interior_fraction() * 100.0) }
+                    }
+                </span>
+                <span>
+                    { format_throughput(stats.iterations_per_second()) }
+                </span>
+            </div>
+        </div>
+    }
+}
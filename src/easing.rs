@@ -0,0 +1,204 @@
+//! Shaping how colors change between gradient pivots.
+
+use serde::Deserialize;
+use serde::Serialize;
+
+/// How colors change along the segment of a gradient leading up to a pivot.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub enum Easing {
+    #[default]
+    Linear,
+
+    /// Start and end slowly, with no sudden change of pace at either pivot.
+    Smoothstep,
+
+    /// Start slowly and end quickly.
+    EaseIn,
+
+    /// Start quickly and end slowly.
+    EaseOut,
+
+    /// Keep the color of the start of the segment until the pivot.
+    Step,
+}
+
+impl Easing {
+    pub const ALL: [Self; 5] = [
+        Self::Linear,
+        Self::Smoothstep,
+        Self::EaseIn,
+        Self::EaseOut,
+        Self::Step,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Linear => "Linear",
+            Self::Smoothstep => "Smoothstep",
+            Self::EaseIn => "Ease in",
+            Self::EaseOut => "Ease out",
+            Self::Step => "Step",
+        }
+    }
+
+    /// How far along the change in color to be at fraction `t` of the way
+    /// along the segment.
+    pub fn apply(&self, t: f64) -> f64 {
+        match self {
+            Self::Linear => t,
+            Self::Smoothstep => t * t * (3.0 - 2.0 * t),
+            Self::EaseIn => t * t,
+            Self::EaseOut => t * (2.0 - t),
+            Self::Step if t < 1.0 => 0.0,
+            Self::Step => 1.0,
+        }
+    }
+}
+
+/// A smooth curve through all the pivots of a gradient, in place of separate
+/// segments.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum Spline {
+    /// Passes through every pivot with a continuous slope, but can overshoot
+    /// between them.
+    CatmullRom,
+
+    /// Like [Spline::CatmullRom], but never overshoots: between two pivots,
+    /// each channel stays between the values at the pivots.
+    MonotoneCubic,
+}
+
+impl Spline {
+    pub const ALL: [Self; 2] = [Self::CatmullRom, Self::MonotoneCubic];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::CatmullRom => "Catmull-Rom spline",
+            Self::MonotoneCubic => "Monotone spline",
+        }
+    }
+
+    /// Values of the curve through knots `(xs[i], ys[i])` at each of
+    /// `targets`. Both `xs` and `targets` must be increasing, `xs` strictly,
+    /// and the curve is flat beyond the first and last knot.
+    pub(crate) fn sample(&self, xs: &[f64], ys: &[f64], targets: &[f64]) -> Vec<f64> {
+        let tangents = self.tangents(xs, ys);
+        let mut segment = 0;
+        targets
+            .iter()
+            .map(|&x| {
+                if x <= xs[0] {
+                    return ys[0];
+                }
+                while segment + 1 < xs.len() && xs[segment + 1] < x {
+                    segment += 1;
+                }
+                if segment + 1 >= xs.len() {
+                    return ys[xs.len() - 1];
+                }
+                hermite(
+                    (xs[segment], xs[segment + 1]),
+                    (ys[segment], ys[segment + 1]),
+                    (tangents[segment], tangents[segment + 1]),
+                    x,
+                )
+            })
+            .collect()
+    }
+
+    /// The slope of the curve at each knot.
+    fn tangents(&self, xs: &[f64], ys: &[f64]) -> Vec<f64> {
+        let n = xs.len();
+        if n < 2 {
+            return vec![0.0; n];
+        }
+        let secants: Vec<f64> = (0..n - 1)
+            .map(|i| (ys[i + 1] - ys[i]) / (xs[i + 1] - xs[i]))
+            .collect();
+
+        match self {
+            Self::CatmullRom => (0..n)
+                .map(|i| match i {
+                    0 => secants[0],
+                    i if i == n - 1 => secants[n - 2],
+                    i => (ys[i + 1] - ys[i - 1]) / (xs[i + 1] - xs[i - 1]),
+                })
+                .collect(),
+            Self::MonotoneCubic => {
+                // Fritsch-Carlson: flat at extrema, and otherwise limited so
+                // that no segment overshoots
+                let mut tangents: Vec<f64> = (0..n)
+                    .map(|i| match i {
+                        0 => secants[0],
+                        i if i == n - 1 => secants[n - 2],
+                        i if secants[i - 1] * secants[i] <= 0.0 => 0.0,
+                        i => (secants[i - 1] + secants[i]) / 2.0,
+                    })
+                    .collect();
+                for (i, secant) in secants.iter().enumerate() {
+                    if *secant == 0.0 {
+                        tangents[i] = 0.0;
+                        tangents[i + 1] = 0.0;
+                        continue;
+                    }
+                    let alpha = tangents[i] / secant;
+                    let beta = tangents[i + 1] / secant;
+                    let length = alpha.hypot(beta);
+                    if length > 3.0 {
+                        tangents[i] = 3.0 * alpha / length * secant;
+                        tangents[i + 1] = 3.0 * beta / length * secant;
+                    }
+                }
+                tangents
+            }
+        }
+    }
+}
+
+/// The cubic Hermite curve between `(x0, y0)` and `(x1, y1)` with slopes `m0`
+/// and `m1` there, at `x`.
+fn hermite((x0, x1): (f64, f64), (y0, y1): (f64, f64), (m0, m1): (f64, f64), x: f64) -> f64 {
+    let h = x1 - x0;
+    let t = (x - x0) / h;
+    let t2 = t * t;
+    let t3 = t2 * t;
+    (2.0 * t3 - 3.0 * t2 + 1.0) * y0
+        + (t3 - 2.0 * t2 + t) * h * m0
+        + (-2.0 * t3 + 3.0 * t2) * y1
+        + (t3 - t2) * h * m1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Easing;
+    use super::Spline;
+
+    #[test]
+    fn easings_span_segment() {
+        for easing in Easing::ALL {
+            assert_eq!(easing.apply(0.0), 0.0, "{easing:?}");
+            assert_eq!(easing.apply(1.0), 1.0, "{easing:?}");
+        }
+        assert_eq!(Easing::Smoothstep.apply(0.5), 0.5);
+        assert!(Easing::EaseIn.apply(0.5) < 0.5);
+        assert!(Easing::EaseOut.apply(0.5) > 0.5);
+        assert_eq!(Easing::Step.apply(0.99), 0.0);
+    }
+
+    #[test]
+    fn splines_pass_through_knots() {
+        let xs = [0.0, 10.0, 15.0, 40.0];
+        let ys = [0.0, 1.0, 1.0, 0.2];
+        for spline in Spline::ALL {
+            assert_eq!(spline.sample(&xs, &ys, &xs), ys.to_vec(), "{spline:?}");
+            assert_eq!(spline.sample(&xs, &ys, &[-5.0, 50.0]), vec![0.0, 0.2]);
+        }
+
+        // Between the two equal knots, only the Catmull-Rom spline overshoots
+        let between: Vec<f64> = (100..150).map(|x| f64::from(x) / 10.0).collect();
+        let catmull_rom = Spline::CatmullRom.sample(&xs, &ys, &between);
+        let monotone = Spline::MonotoneCubic.sample(&xs, &ys, &between);
+        assert!(catmull_rom.iter().any(|y| *y > 1.0));
+        assert!(monotone.iter().all(|y| (0.0..=1.0).contains(y)));
+    }
+}
@@ -0,0 +1,260 @@
+//! The exponential map, for rendering zoom videos quickly: one tall strip in
+//! log-polar coordinates around the point being zoomed into holds every
+//! frame of the zoom, and each frame is resampled from the strip rather than
+//! rendered from scratch.
+
+use std::f64::consts::TAU;
+
+use crate::complex::Complex;
+
+/// A strip in log-polar coordinates around `center`. Columns go once around
+/// the center, and rows go inwards from `radius`, each row closer to the
+/// center by the same factor, so that pixels stay square.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ExpStrip {
+    center: Complex<f64>,
+    radius: f64,
+    width: usize,
+    height: usize,
+}
+
+impl ExpStrip {
+    pub fn new((re, im): (f64, f64), radius: f64, width: usize, height: usize) -> Self {
+        Self {
+            center: Complex::from((re, im)),
+            radius,
+            width: width.max(1),
+            height,
+        }
+    }
+
+    /// A strip for zooming from `from_scale` to `to_scale` into `center`,
+    /// with frames of `frame_width` by `frame_height`. It reaches from the
+    /// corners of the first frame to the pixel at the center of the last.
+    pub fn for_zoom(
+        center: (f64, f64),
+        width: usize,
+        (frame_width, frame_height): (usize, usize),
+        from_scale: f64,
+        to_scale: f64,
+    ) -> Self {
+        let radius = from_scale * (frame_width as f64).hypot(frame_height as f64) / 2.0;
+        let rows_per_e = width.max(1) as f64 / TAU;
+        let height = (rows_per_e * (radius / to_scale).ln()).ceil().max(0.0) as usize + 1;
+        Self::new(center, radius, width, height)
+    }
+
+    /// The strip width at which the corners of a `frame_width` by
+    /// `frame_height` frame are sampled as densely as the frame's pixels.
+    /// Closer to the center, the strip is sampled more densely still.
+    pub fn default_width(frame_width: usize, frame_height: usize) -> usize {
+        (std::f64::consts::PI * (frame_width as f64).hypot(frame_height as f64)).ceil() as usize
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The same strip with `factor` times fewer pixels along each side.
+    pub fn downsampled(&self, factor: usize) -> Self {
+        let factor = factor.max(1);
+        Self::new(
+            (self.center.re, self.center.im),
+            self.radius,
+            self.width / factor,
+            self.height.div_ceil(factor),
+        )
+    }
+
+    /// Rows `y0` to `y0 + height` of the strip, as a strip of their own.
+    pub fn band(&self, y0: usize, height: usize) -> Self {
+        Self {
+            radius: self.row_radius(y0 as f64),
+            height: std::cmp::min(height, self.height.saturating_sub(y0)),
+            ..*self
+        }
+    }
+
+    fn rows_per_e(&self) -> f64 {
+        self.width as f64 / TAU
+    }
+
+    fn row_radius(&self, y: f64) -> f64 {
+        self.radius * (-y / self.rows_per_e()).exp()
+    }
+
+    /// The point at pixel `(x, y)` of the strip.
+    pub(crate) fn point(&self, x: i32, y: i32) -> Complex<f64> {
+        let angle = TAU * x as f64 / self.width as f64;
+        let radius = self.row_radius(y as f64);
+        self.center + Complex::from((radius * angle.cos(), radius * angle.sin()))
+    }
+
+    /// The position in the strip, in fractional pixels, of the point at
+    /// `(re, im)` from the center.
+    fn locate(&self, re: f64, im: f64) -> (f64, f64) {
+        let angle = im.atan2(re).rem_euclid(TAU);
+        let x = angle * self.rows_per_e();
+        let y = (self.radius / re.hypot(im)).ln() * self.rows_per_e();
+        (x, y)
+    }
+
+    /// Sample the RGBA pixels of the strip at a fractional position,
+    /// interpolating between the four nearest pixels. Columns wrap around,
+    /// and rows past either end repeat the last row.
+    fn sample(&self, rgba: &[u8], x: f64, y: f64) -> [f64; 4] {
+        let y = y.clamp(0.0, self.height.saturating_sub(1) as f64);
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let col = |dx: usize| (x0 as i64 + dx as i64).rem_euclid(self.width as i64) as usize;
+        let row = |dy: usize| std::cmp::min(y0 as usize + dy, self.height - 1);
+
+        let mut color = [0.0; 4];
+        for (dx, dy, weight) in [
+            (0, 0, (1.0 - fx) * (1.0 - fy)),
+            (1, 0, fx * (1.0 - fy)),
+            (0, 1, (1.0 - fx) * fy),
+            (1, 1, fx * fy),
+        ] {
+            let i = (col(dx) + row(dy) * self.width) * 4;
+            for (c, value) in color.iter_mut().zip(&rgba[i..i + 4]) {
+                *c += f64::from(*value) * weight;
+            }
+        }
+        color
+    }
+
+    /// Resample the rendered strip, as RGBA bytes row by row, into a frame
+    /// of `frame_width` by `frame_height` at `scale` around the center.
+    /// Pixels are placed as [crate::Engine] places them, and each averages
+    /// the part of the strip it covers.
+    pub fn reconstruct(
+        &self,
+        rgba: &[u8],
+        frame_width: usize,
+        frame_height: usize,
+        scale: f64,
+    ) -> Vec<u8> {
+        /// Most samples per frame pixel along each axis, for the center of
+        /// the frame, where the strip is far denser than the frame
+        const MAX_SAMPLES: usize = 4;
+
+        let mut frame = Vec::with_capacity(frame_width * frame_height * 4);
+        if self.height == 0 {
+            frame.resize(frame_width * frame_height * 4, 0);
+            return frame;
+        }
+
+        for y in 0..frame_height {
+            for x in 0..frame_width {
+                let re = (x as f64 - frame_width as f64 / 2.0) * scale;
+                let im = (frame_height as f64 / 2.0 - y as f64) * scale;
+                let (sx, sy) = self.locate(re, im);
+
+                // The strip is conformal, so a frame pixel covers a square
+                // of strip pixels
+                let footprint = self.rows_per_e() * scale / re.hypot(im).max(f64::MIN_POSITIVE);
+                let samples = (footprint.ceil() as usize).clamp(1, MAX_SAMPLES);
+                let step = footprint.min(self.width as f64) / samples as f64;
+                let start = -step * (samples - 1) as f64 / 2.0;
+
+                let mut color = [0.0; 4];
+                for i in 0..samples {
+                    for j in 0..samples {
+                        let sample = self.sample(
+                            rgba,
+                            sx + start + step * i as f64,
+                            sy + start + step * j as f64,
+                        );
+                        for (c, s) in color.iter_mut().zip(sample) {
+                            *c += s;
+                        }
+                    }
+                }
+                let count = (samples * samples) as f64;
+                frame.extend(color.map(|c| (c / count).round() as u8));
+            }
+        }
+        frame
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ExpStrip;
+    use crate::presets::PRESETS;
+    use crate::Engine;
+    use crate::EngineSettings;
+
+    #[test]
+    fn strip_geometry() {
+        let strip = ExpStrip::for_zoom((-0.5, 0.25), 64, (40, 30), 0.1, 0.001);
+        assert_eq!(ExpStrip::default_width(30, 40), 158);
+
+        // Rows shrink by e every width / τ rows
+        let outer = strip.point(0, 0);
+        assert!((outer.re - (-0.5 + 2.5)).abs() < 1e-12);
+        assert!((outer.im - 0.25).abs() < 1e-12);
+        assert!(strip.row_radius(strip.height as f64 - 1.0) <= 0.001);
+
+        for (x, y) in [(0, 0), (10, 3), (63, 40)] {
+            let point = strip.point(x, y);
+            let (sx, sy) = strip.locate(point.re + 0.5, point.im - 0.25);
+            assert!((sx - x as f64).abs() < 1e-9, "{sx} != {x}");
+            assert!((sy - y as f64).abs() < 1e-9, "{sy} != {y}");
+        }
+
+        let band = strip.band(10, 1000);
+        assert_eq!(band.height, strip.height - 10);
+        assert!((band.point(5, 0).re - strip.point(5, 10).re).abs() < 1e-12);
+
+        // A downsampled strip reaches as far in
+        let preview = strip.downsampled(4);
+        assert_eq!(
+            (preview.width, preview.height),
+            (16, strip.height.div_ceil(4))
+        );
+        assert!(preview.row_radius(preview.height as f64 - 1.0) <= 0.001 * 1.5);
+    }
+
+    #[test]
+    fn reconstructs_frames() {
+        let (width, height) = (128, 96);
+        let mut settings = EngineSettings::restore(PRESETS[1].state)
+            .unwrap()
+            .set_size(width, height);
+        let scale = settings.get_scale();
+
+        let mut engine = Engine::new(&settings);
+        engine.apply_settings(&mut settings);
+        engine.compute(usize::MAX);
+        engine.render();
+
+        let strip = ExpStrip::for_zoom(
+            settings.get_center(),
+            2 * ExpStrip::default_width(width, height),
+            (width, height),
+            scale,
+            scale / 4.0,
+        );
+        let mut strip_engine = Engine::new_strip(&settings, &strip);
+        strip_engine.compute(usize::MAX);
+        strip_engine.render();
+        let frame = strip.reconstruct(strip_engine.image_data(), width, height, scale);
+        assert_eq!(frame.len(), width * height * 4);
+
+        // Resampling blurs edges, but on average pixels come out nearly the
+        // same
+        let difference: usize = frame
+            .iter()
+            .zip(engine.image_data())
+            .map(|(a, b)| a.abs_diff(*b) as usize)
+            .sum();
+        let mean = difference as f64 / frame.len() as f64;
+        assert!(mean < 3.0, "mean difference {mean}");
+    }
+}
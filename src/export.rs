@@ -0,0 +1,475 @@
+//! Writing rendered images to files, and restoring settings from them.
+
+use std::error::Error;
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::io::Write;
+use std::time::Duration;
+
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use flate2::Crc;
+
+use crate::Engine;
+use crate::EngineSettings;
+
+/// Keyword of the PNG text chunk holding [EngineSettings::serialize].
+const STATE_KEYWORD: &str = "fraktal:state";
+
+/// Encode an RGBA image, as returned by [crate::Engine::image_data], as PNG.
+/// If `settings` are given, they are embedded in text chunks so that
+/// [read_png_settings] can restore them, along with a human-readable
+/// description of the view.
+pub fn write_png(
+    writer: impl Write,
+    width: usize,
+    height: usize,
+    rgba: &[u8],
+    settings: Option<&EngineSettings>,
+) -> Result<(), png::EncodingError> {
+    let mut writer = png_writer(writer, width, height, settings)?;
+    writer.write_image_data(rgba)?;
+    writer.finish()
+}
+
+/// Like [write_png], but return the writer after the header, so that the
+/// image data can be written a few rows at a time with
+/// [png::Writer::stream_writer].
+pub fn png_writer<W: Write>(
+    writer: W,
+    width: usize,
+    height: usize,
+    settings: Option<&EngineSettings>,
+) -> Result<png::Writer<W>, png::EncodingError> {
+    png_encoder(writer, width, height, settings)?.write_header()
+}
+
+fn png_encoder<W: Write>(
+    writer: W,
+    width: usize,
+    height: usize,
+    settings: Option<&EngineSettings>,
+) -> Result<png::Encoder<'static, W>, png::EncodingError> {
+    let mut encoder = png::Encoder::new(writer, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    encoder.add_text_chunk("Software".to_string(), "fraktal".to_string())?;
+    if let Some(settings) = settings {
+        if let Some(state) = settings.serialize() {
+            encoder.add_text_chunk(STATE_KEYWORD.to_string(), state)?;
+        }
+        let (re, im) = settings.get_center();
+        encoder.add_itxt_chunk("Center".to_string(), format!("{re} {im:+} i"))?;
+        encoder.add_itxt_chunk(
+            "Scale".to_string(),
+            format!("{:e} per pixel", settings.get_scale()),
+        )?;
+        encoder.add_itxt_chunk(
+            "Iteration limit".to_string(),
+            settings.get_iteration_limit().to_string(),
+        )?;
+    }
+
+    Ok(encoder)
+}
+
+/// How far a [PngRowWriter] got: the length of the file, and the checksum of
+/// the rows written so far.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PngPosition {
+    pub len: u64,
+    pub adler: u32,
+}
+
+/// Writes a PNG file a few rows at a time, like [png::StreamWriter], but can
+/// also resume writing after an interruption, at a [PngPosition] that it
+/// returned. Each call to [PngRowWriter::write_rows] ends the compressed
+/// data on a byte boundary, so that a new compressor can carry on from
+/// there.
+pub struct PngRowWriter {
+    file: File,
+    width: usize,
+    encoder: DeflateEncoder<Vec<u8>>,
+    adler: u32,
+}
+
+impl PngRowWriter {
+    /// The chunk that ends every PNG file.
+    const IEND: [u8; 12] = [0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82];
+
+    /// Start a PNG file with the header and text chunks of [write_png].
+    pub fn create(
+        mut file: File,
+        width: usize,
+        height: usize,
+        settings: Option<&EngineSettings>,
+    ) -> Result<Self, png::EncodingError> {
+        // The png writer ends the file when dropped, so its header is written
+        // to memory first and the end cut off
+        let mut header = Vec::new();
+        drop(png_writer(&mut header, width, height, settings)?);
+        debug_assert!(header.ends_with(&Self::IEND));
+        header.truncate(header.len() - Self::IEND.len());
+        file.write_all(&header)?;
+
+        // The zlib header, for the default compression level
+        write_idat(&mut file, &[0x78, 0x9c])?;
+        Ok(Self::new(file, width))
+    }
+
+    /// Carry on writing a file that [PngRowWriter::create] started, after
+    /// the rows written up to `position`.
+    pub fn resume(mut file: File, width: usize, position: PngPosition) -> io::Result<Self> {
+        if file.metadata()?.len() < position.len {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "the file is shorter than the rows written to it",
+            ));
+        }
+        file.set_len(position.len)?;
+        file.seek(SeekFrom::End(0))?;
+        Ok(Self {
+            adler: position.adler,
+            ..Self::new(file, width)
+        })
+    }
+
+    fn new(file: File, width: usize) -> Self {
+        Self {
+            file,
+            width,
+            encoder: DeflateEncoder::new(Vec::new(), Compression::default()),
+            adler: 1,
+        }
+    }
+
+    /// Write whole rows of RGBA pixels, and return the position after them
+    /// once they are written to the file.
+    pub fn write_rows(&mut self, rgba: &[u8]) -> io::Result<PngPosition> {
+        let row_len = self.width * 4;
+        let mut filtered = vec![0; row_len + 1];
+        for row in rgba.chunks_exact(row_len) {
+            // The sub filter stores each byte as its difference from the byte
+            // of the pixel to its left
+            filtered[0] = 1;
+            for i in 0..row_len {
+                let left = if i >= 4 { row[i - 4] } else { 0 };
+                filtered[i + 1] = row[i].wrapping_sub(left);
+            }
+            self.adler = adler32(self.adler, &filtered);
+            self.encoder.write_all(&filtered)?;
+        }
+        self.encoder.flush()?;
+        write_idat(&mut self.file, self.encoder.get_ref())?;
+        self.encoder.get_mut().clear();
+        self.file.flush()?;
+
+        Ok(PngPosition {
+            len: self.file.stream_position()?,
+            adler: self.adler,
+        })
+    }
+
+    /// End the compressed data and the file, once all rows are written.
+    pub fn finish(self) -> io::Result<()> {
+        let Self {
+            mut file,
+            encoder,
+            adler,
+            ..
+        } = self;
+        let mut compressed = encoder.finish()?;
+        compressed.extend(adler.to_be_bytes());
+        write_idat(&mut file, &compressed)?;
+        file.write_all(&Self::IEND)?;
+        file.flush()
+    }
+}
+
+/// Write a chunk of compressed image data.
+fn write_idat(writer: &mut impl Write, data: &[u8]) -> io::Result<()> {
+    let mut crc = Crc::new();
+    crc.update(b"IDAT");
+    crc.update(data);
+    writer.write_all(&(data.len() as u32).to_be_bytes())?;
+    writer.write_all(b"IDAT")?;
+    writer.write_all(data)?;
+    writer.write_all(&crc.sum().to_be_bytes())
+}
+
+/// Continue the Adler-32 checksum `adler` of a zlib stream over `data`.
+fn adler32(adler: u32, data: &[u8]) -> u32 {
+    const MOD: u32 = 65521;
+    let (mut a, mut b) = (adler & 0xffff, adler >> 16);
+    // The most bytes that can be summed before the sums could overflow
+    for chunk in data.chunks(5552) {
+        for byte in chunk {
+            a += u32::from(*byte);
+            b += a;
+        }
+        a %= MOD;
+        b %= MOD;
+    }
+    (b << 16) | a
+}
+
+/// Encode one full cycle of the colors of `engine`, as set by
+/// [Engine::set_palette_phase], as an animated PNG that loops forever. The
+/// cycle is split into at most `frames` evenly spaced phases, each shown
+/// for `frame_delay`. The engine should be done computing, and is left at
+/// phase 0.
+pub fn write_palette_cycle(
+    writer: impl Write,
+    engine: &mut Engine,
+    frames: usize,
+    frame_delay: Duration,
+    settings: Option<&EngineSettings>,
+) -> Result<(), png::EncodingError> {
+    let cycle_length = engine.palette_cycle_length();
+    let frames = frames.clamp(1, cycle_length);
+    let delay_ms = frame_delay.as_millis().clamp(1, u16::MAX.into()) as u16;
+
+    let mut encoder = png_encoder(writer, engine.get_width(), engine.get_height(), settings)?;
+    encoder.set_animated(frames as u32, 0)?;
+    encoder.set_frame_delay(delay_ms, 1000)?;
+    let mut writer = encoder.write_header()?;
+    for frame in 0..frames {
+        engine.set_palette_phase(frame * cycle_length / frames);
+        engine.render();
+        writer.write_image_data(engine.image_data())?;
+    }
+    engine.set_palette_phase(0);
+    engine.render();
+    writer.finish()
+}
+
+/// Find the serialized settings embedded by [write_png].
+pub fn read_png_state(reader: impl Read) -> Result<String, Box<dyn Error>> {
+    let reader = png::Decoder::new(reader).read_info()?;
+    let info = reader.info();
+
+    if let Some(chunk) = info
+        .uncompressed_latin1_text
+        .iter()
+        .find(|chunk| chunk.keyword == STATE_KEYWORD)
+    {
+        return Ok(chunk.text.clone());
+    }
+    if let Some(chunk) = info
+        .compressed_latin1_text
+        .iter()
+        .find(|chunk| chunk.keyword == STATE_KEYWORD)
+    {
+        return Ok(chunk.get_text()?);
+    }
+    if let Some(chunk) = info
+        .utf8_text
+        .iter()
+        .find(|chunk| chunk.keyword == STATE_KEYWORD)
+    {
+        return Ok(chunk.get_text()?);
+    }
+    Err("No fraktal settings found in PNG file".into())
+}
+
+/// Restore the settings embedded by [write_png].
+pub fn read_png_settings(reader: impl Read) -> Result<EngineSettings, Box<dyn Error>> {
+    EngineSettings::try_restore(&read_png_state(reader)?)
+}
+
+/// Writes RGBA frames as an uncompressed YUV4MPEG2 stream, which video
+/// encoders such as ffmpeg can read from a file or a pipe.
+pub struct Y4mWriter<W: Write> {
+    writer: W,
+    width: usize,
+    height: usize,
+    planes: Vec<u8>,
+}
+
+impl<W: Write> Y4mWriter<W> {
+    /// Write the stream header. Frames are stored in full resolution 4:4:4,
+    /// with BT.601 limited range colors.
+    pub fn new(mut writer: W, width: usize, height: usize, fps: usize) -> std::io::Result<Self> {
+        writeln!(writer, "YUV4MPEG2 W{width} H{height} F{fps}:1 Ip A1:1 C444")?;
+        Ok(Self {
+            writer,
+            width,
+            height,
+            planes: vec![0; width * height * 3],
+        })
+    }
+
+    /// Write one frame, as returned by [crate::Engine::image_data].
+    pub fn write_frame(&mut self, rgba: &[u8]) -> std::io::Result<()> {
+        let pixels = self.width * self.height;
+        let (y, uv) = self.planes.split_at_mut(pixels);
+        let (u, v) = uv.split_at_mut(pixels);
+        for (i, rgba) in rgba.chunks_exact(4).take(pixels).enumerate() {
+            let (r, g, b) = (f64::from(rgba[0]), f64::from(rgba[1]), f64::from(rgba[2]));
+            y[i] = (16.0 + (65.481 * r + 128.553 * g + 24.966 * b) / 255.0).round() as u8;
+            u[i] = (128.0 + (-37.797 * r - 74.203 * g + 112.0 * b) / 255.0).round() as u8;
+            v[i] = (128.0 + (112.0 * r - 93.786 * g - 18.214 * b) / 255.0).round() as u8;
+        }
+        self.writer.write_all(b"FRAME\n")?;
+        self.writer.write_all(&self.planes)
+    }
+
+    pub fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::read_png_settings;
+    use super::read_png_state;
+    use super::write_palette_cycle;
+    use super::write_png;
+    use super::PngRowWriter;
+    use super::Y4mWriter;
+    use crate::presets::PRESETS;
+    use crate::Engine;
+    use crate::EngineSettings;
+
+    #[test]
+    fn png_round_trip() {
+        let rgba: Vec<u8> = (0..4 * 3 * 2).map(|i| i as u8).collect();
+        let mut encoded = Vec::new();
+        write_png(&mut encoded, 3, 2, &rgba, None).unwrap();
+
+        let mut reader = png::Decoder::new(encoded.as_slice()).read_info().unwrap();
+        let mut decoded = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut decoded).unwrap();
+        assert_eq!((info.width, info.height), (3, 2));
+        assert_eq!(decoded, rgba);
+        assert!(read_png_state(encoded.as_slice()).is_err());
+    }
+
+    #[test]
+    fn settings_round_trip() {
+        let settings = EngineSettings::restore(PRESETS[1].state).unwrap();
+        let mut encoded = Vec::new();
+        write_png(&mut encoded, 1, 1, &[0; 4], Some(&settings)).unwrap();
+
+        assert_eq!(
+            read_png_state(encoded.as_slice()).unwrap(),
+            PRESETS[1].state
+        );
+        let restored = read_png_settings(encoded.as_slice()).unwrap();
+        assert_eq!(restored.serialize(), settings.serialize());
+        assert_eq!(restored.get_center(), settings.get_center());
+
+        let reader = png::Decoder::new(encoded.as_slice()).read_info().unwrap();
+        let keywords: Vec<&str> = reader
+            .info()
+            .utf8_text
+            .iter()
+            .map(|chunk| chunk.keyword.as_str())
+            .collect();
+        assert_eq!(keywords, ["Center", "Scale", "Iteration limit"]);
+    }
+
+    #[test]
+    fn png_rows_resume() {
+        let path = std::env::temp_dir().join(format!("fraktal-rows-test-{}", std::process::id()));
+        let (width, height) = (5, 6);
+        let rgba: Vec<u8> = (0..4 * width * height).map(|i| (i * 7) as u8).collect();
+        let settings = EngineSettings::restore(PRESETS[1].state).unwrap();
+
+        let mut writer = PngRowWriter::create(
+            std::fs::File::create(&path).unwrap(),
+            width,
+            height,
+            Some(&settings),
+        )
+        .unwrap();
+        let position = writer.write_rows(&rgba[..4 * width * 2]).unwrap();
+        // Interrupted partway through the next rows
+        writer.write_rows(&[0; 4 * 5]).unwrap();
+        drop(writer);
+
+        let file = std::fs::OpenOptions::new().write(true).open(&path).unwrap();
+        let mut writer = PngRowWriter::resume(file, width, position).unwrap();
+        writer
+            .write_rows(&rgba[4 * width * 2..4 * width * 5])
+            .unwrap();
+        writer.write_rows(&rgba[4 * width * 5..]).unwrap();
+        writer.finish().unwrap();
+
+        let encoded = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let mut reader = png::Decoder::new(encoded.as_slice()).read_info().unwrap();
+        let mut decoded = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut decoded).unwrap();
+        assert_eq!((info.width, info.height), (width as u32, height as u32));
+        assert_eq!(decoded, rgba);
+        assert_eq!(
+            read_png_state(encoded.as_slice()).unwrap(),
+            PRESETS[1].state
+        );
+    }
+
+    #[test]
+    fn y4m_frames() {
+        let mut encoded = Vec::new();
+        let mut writer = Y4mWriter::new(&mut encoded, 3, 2, 30).unwrap();
+        let black = [0, 0, 0, 255].repeat(6);
+        let white = [255; 4 * 6];
+        writer.write_frame(&black).unwrap();
+        writer.write_frame(&white).unwrap();
+        drop(writer);
+
+        let header = b"YUV4MPEG2 W3 H2 F30:1 Ip A1:1 C444\n";
+        assert!(encoded.starts_with(header));
+        let frame_len = b"FRAME\n".len() + 3 * 6;
+        assert_eq!(encoded.len(), header.len() + 2 * frame_len);
+
+        let first = &encoded[header.len() + 6..header.len() + frame_len];
+        assert_eq!(&first[..6], &[16; 6]);
+        assert_eq!(&first[6..], &[128; 12]);
+        let second = &encoded[header.len() + frame_len + 6..];
+        assert_eq!(&second[..6], &[235; 6]);
+        assert_eq!(&second[6..], &[128; 12]);
+    }
+
+    #[test]
+    fn palette_cycle() {
+        let mut settings = EngineSettings::new()
+            .set_view((-0.75, 0.1), 0.2)
+            .set_size(16, 12)
+            .set_iteration_limit(40);
+        let mut engine = Engine::new(&settings);
+        engine.apply_settings(&mut settings);
+        engine.compute(usize::MAX);
+        engine.render();
+        let still = engine.image_data().to_vec();
+
+        let mut encoded = Vec::new();
+        write_palette_cycle(
+            &mut encoded,
+            &mut engine,
+            8,
+            std::time::Duration::from_millis(40),
+            None,
+        )
+        .unwrap();
+        assert_eq!(engine.image_data(), still);
+
+        let mut reader = png::Decoder::new(encoded.as_slice()).read_info().unwrap();
+        let animation = reader.info().animation_control.unwrap();
+        assert_eq!((animation.num_frames, animation.num_plays), (8, 0));
+
+        let mut frames = Vec::new();
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        while reader.next_frame(&mut buffer).is_ok() {
+            frames.push(buffer.clone());
+        }
+        assert_eq!(frames.len(), 8);
+        assert_eq!(frames[0], still);
+        assert!(frames[1..].iter().all(|frame| *frame != still));
+    }
+}
@@ -0,0 +1,555 @@
+//! Reading and writing gradients in the formats of other programs.
+
+use std::error::Error;
+use std::fmt::Write;
+
+use crate::color_space::InterpolationSpace;
+use crate::easing::Easing;
+use crate::Color;
+use crate::Gradient;
+use crate::GradientPivot;
+use crate::Spread;
+use crate::Transfer;
+
+/// How many stops to sample from a gradient that the other formats can't
+/// express as its pivots, such as a spline.
+const EXPORT_SAMPLES: usize = 64;
+
+/// The number of colors in a Fractint map.
+const MAP_COLORS: usize = 256;
+
+/// The largest difference in any channel between a color of a Fractint map
+/// and the gradient imported from it.
+const MAP_TOLERANCE: i32 = 2;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GradientFormat {
+    /// A GIMP `.ggr` gradient, made of blended segments.
+    Ggr,
+
+    /// A Fractint or XaoS `.map` palette of 256 colors.
+    Map,
+
+    /// A CSS `linear-gradient()` with color stops.
+    Css,
+}
+
+impl GradientFormat {
+    pub const ALL: [Self; 3] = [Self::Ggr, Self::Map, Self::Css];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Ggr => "GIMP gradient",
+            Self::Map => "Fractint map",
+            Self::Css => "CSS gradient",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Ggr => "ggr",
+            Self::Map => "map",
+            Self::Css => "css",
+        }
+    }
+
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            Self::Ggr | Self::Map => "text/plain",
+            Self::Css => "text/css",
+        }
+    }
+
+    /// The format of a file named `name`, judging by its extension.
+    pub fn from_file_name(name: &str) -> Option<Self> {
+        let (_, extension) = name.rsplit_once('.')?;
+        Self::ALL
+            .into_iter()
+            .find(|format| format.extension().eq_ignore_ascii_case(extension))
+    }
+
+    /// Pivots from a file in this format, with values as fractions of the
+    /// way along the gradient in `[0, 1]`.
+    pub fn read(&self, text: &str) -> Result<Vec<GradientPivot>, Box<dyn Error>> {
+        let pivots = match self {
+            Self::Ggr => read_ggr(text)?,
+            Self::Map => read_map(text)?,
+            Self::Css => read_css(text)?,
+        };
+        if pivots.is_empty() {
+            Err(format!("No colors in {}", self.name()).into())
+        } else {
+            Ok(pivots)
+        }
+    }
+
+    /// `gradient` in this format, as it looks up to `iteration_limit`.
+    pub fn write(&self, gradient: &Gradient, iteration_limit: usize) -> String {
+        match self {
+            Self::Ggr => write_ggr(&export_pivots(gradient, iteration_limit)),
+            Self::Map => write_map(gradient, iteration_limit),
+            Self::Css => write_css(&export_pivots(gradient, iteration_limit)),
+        }
+    }
+}
+
+/// Pivots at fractions of the iteration limit that look like `gradient`,
+/// starting at zero and ending at one. Pivots are sampled from the palette
+/// when plain segments can't reproduce it.
+fn export_pivots(gradient: &Gradient, iteration_limit: usize) -> Vec<GradientPivot> {
+    let plain = gradient.get_spline().is_none()
+        && gradient.get_interpolation() == InterpolationSpace::Srgb
+        && gradient.get_spread() == Spread::Pad;
+
+    let mut pivots: Vec<GradientPivot> = if plain {
+        gradient
+            .get_pivots()
+            .iter()
+            .map(|pivot| GradientPivot {
+                value: gradient.pivot_fraction(pivot, iteration_limit).min(1.0),
+                ..pivot.clone()
+            })
+            .collect()
+    } else {
+        gradient
+            .ribbon_colors(iteration_limit, &Transfer::Identity, EXPORT_SAMPLES)
+            .into_iter()
+            .enumerate()
+            .map(|(i, color)| GradientPivot::new(i as f64 / EXPORT_SAMPLES as f64, color))
+            .collect()
+    };
+
+    if let Some(first) = pivots.first().filter(|first| first.value > 0.0) {
+        pivots.insert(0, GradientPivot::new(0.0, first.color));
+    }
+    if let Some(last) = pivots.last().filter(|last| last.value < 1.0) {
+        pivots.push(GradientPivot::new(1.0, last.color));
+    }
+    pivots
+}
+
+/// Easings for GIMP's blending functions, which only roughly match.
+const GGR_BLENDS: [(u8, Easing); 6] = [
+    (0, Easing::Linear),
+    (1, Easing::Linear),
+    (2, Easing::Smoothstep),
+    (3, Easing::EaseOut),
+    (4, Easing::EaseIn),
+    (5, Easing::Step),
+];
+
+fn read_ggr(text: &str) -> Result<Vec<GradientPivot>, Box<dyn Error>> {
+    let mut lines = text.lines().map(str::trim).filter(|line| !line.is_empty());
+    if lines.next() != Some("GIMP Gradient") {
+        return Err("Not a GIMP gradient".into());
+    }
+    let mut count_line = lines.next().ok_or("Missing segment count")?;
+    if count_line.starts_with("Name:") {
+        count_line = lines.next().ok_or("Missing segment count")?;
+    }
+    let count: usize = count_line.parse()?;
+
+    let mut pivots: Vec<GradientPivot> = Vec::new();
+    for _ in 0..count {
+        let line = lines.next().ok_or("Missing segment")?;
+        let numbers = line
+            .split_whitespace()
+            .map(str::parse)
+            .collect::<Result<Vec<f64>, _>>()?;
+        let [left, middle, right, r0, g0, b0, a0, r1, g1, b1, a1, blend, ..] = numbers[..] else {
+            return Err(format!("Short segment: {line}").into());
+        };
+        let channel = |c: f64| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+        let start = Color::of(channel(r0), channel(g0), channel(b0), channel(a0));
+        let end = Color::of(channel(r1), channel(g1), channel(b1), channel(a1));
+        let easing = GGR_BLENDS
+            .iter()
+            .find(|(code, _)| f64::from(*code) == blend)
+            .map_or(Easing::Linear, |(_, easing)| *easing);
+
+        if pivots
+            .last()
+            .is_none_or(|last| last.value != left || last.color != start)
+        {
+            pivots.push(GradientPivot::new(left, start));
+        }
+        // An off-center midpoint is where the segment is halfway blended
+        if (middle - (left + right) / 2.0).abs() > 1e-6 && easing != Easing::Step {
+            pivots.push(GradientPivot {
+                easing,
+                ..GradientPivot::new(
+                    middle,
+                    InterpolationSpace::Srgb.interpolate_at(&start, &end, easing.apply(0.5)),
+                )
+            });
+        }
+        pivots.push(GradientPivot {
+            easing,
+            ..GradientPivot::new(right, end)
+        });
+    }
+    Ok(pivots)
+}
+
+fn write_ggr(pivots: &[GradientPivot]) -> String {
+    let mut ggr = String::from("GIMP Gradient\nName: fraktal\n");
+    let segments: Vec<_> = pivots
+        .windows(2)
+        .filter(|w| w[0].value < w[1].value)
+        .collect();
+    let _ = writeln!(ggr, "{}", segments.len());
+    for pair in segments {
+        let [start, end] = [&pair[0], &pair[1]];
+        let blend = GGR_BLENDS
+            .iter()
+            .find(|(code, easing)| *code != 1 && *easing == end.easing)
+            .map_or(0, |(code, _)| *code);
+        let channels = |color: &Color| {
+            color
+                .as_rgba()
+                .map(|c| format!("{:.6}", f64::from(c) / 255.0))
+                .join(" ")
+        };
+        let _ = writeln!(
+            ggr,
+            "{:.6} {:.6} {:.6} {} {} {blend} 0",
+            start.value,
+            (start.value + end.value) / 2.0,
+            end.value,
+            channels(&start.color),
+            channels(&end.color),
+        );
+    }
+    ggr
+}
+
+fn read_map(text: &str) -> Result<Vec<GradientPivot>, Box<dyn Error>> {
+    let mut colors = Vec::new();
+    for line in text.lines() {
+        let mut words = line.split_whitespace();
+        let mut channel = || words.next().and_then(|word| word.parse::<u8>().ok());
+        if let (Some(r), Some(g), Some(b)) = (channel(), channel(), channel()) {
+            colors.push(Color::of(r, g, b, 255));
+        } else if !line.trim().is_empty() {
+            return Err(format!("Not a color: {line}").into());
+        }
+    }
+    Ok(simplify(&colors))
+}
+
+/// Pivots evenly spaced from zero to one with `colors`, leaving out those the
+/// gradient would come close to anyway.
+fn simplify(colors: &[Color]) -> Vec<GradientPivot> {
+    let last = colors.len().saturating_sub(1).max(1) as f64;
+    let close = |from: usize, to: usize| {
+        (from + 1..to).all(|i| {
+            let expected = Color::lerp(
+                &colors[from],
+                &colors[to],
+                from as isize,
+                to as isize,
+                i as isize,
+            );
+            expected
+                .as_rgba()
+                .iter()
+                .zip(colors[i].as_rgba())
+                .all(|(e, c)| (i32::from(*e) - i32::from(c)).abs() <= MAP_TOLERANCE)
+        })
+    };
+
+    let mut kept = Vec::new();
+    let mut from = 0;
+    for (i, color) in colors.iter().enumerate() {
+        let is_end = i == 0 || i + 1 == colors.len();
+        if is_end || !close(from, i + 1) {
+            kept.push(GradientPivot::new(i as f64 / last, *color));
+            from = i;
+        }
+    }
+    kept
+}
+
+fn write_map(gradient: &Gradient, iteration_limit: usize) -> String {
+    gradient
+        .ribbon_colors(iteration_limit, &Transfer::Identity, MAP_COLORS)
+        .iter()
+        .map(|color| {
+            let [r, g, b, _] = color.as_rgba();
+            format!("{r:3} {g:3} {b:3}\n")
+        })
+        .collect()
+}
+
+/// Split at commas outside parentheses.
+fn split_arguments(text: &str) -> Vec<&str> {
+    let mut depth = 0;
+    let mut start = 0;
+    let mut arguments = Vec::new();
+    for (i, c) in text.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                arguments.push(text[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    arguments.push(text[start..].trim());
+    arguments
+}
+
+fn parse_css_color(text: &str) -> Result<Color, Box<dyn Error>> {
+    if let Some(hex) = text.strip_prefix('#') {
+        let digits: Vec<u8> = hex
+            .chars()
+            .map(|c| c.to_digit(16).map(|d| d as u8).ok_or("Bad hex digit"))
+            .collect::<Result<_, _>>()?;
+        return match digits[..] {
+            [r, g, b] => Ok(Color::of(r * 17, g * 17, b * 17, 255)),
+            [r, g, b, a] => Ok(Color::of(r * 17, g * 17, b * 17, a * 17)),
+            _ => Color::parse_hex(text).ok_or_else(|| format!("Unsupported color: {text}").into()),
+        };
+    }
+
+    let arguments = text
+        .strip_prefix("rgba(")
+        .or_else(|| text.strip_prefix("rgb("))
+        .and_then(|rest| rest.strip_suffix(')'))
+        .ok_or_else(|| format!("Unsupported color: {text}"))?;
+    let numbers = arguments
+        .split([',', ' ', '/'])
+        .filter(|word| !word.is_empty())
+        .map(|word| match word.strip_suffix('%') {
+            Some(percent) => percent.parse::<f64>().map(|p| p / 100.0),
+            None => word.parse::<f64>(),
+        })
+        .collect::<Result<Vec<f64>, _>>()?;
+    let channel = |c: f64| c.clamp(0.0, 255.0).round() as u8;
+    match numbers[..] {
+        [r, g, b] => Ok(Color::of(channel(r), channel(g), channel(b), 255)),
+        [r, g, b, a] => Ok(Color::of(
+            channel(r),
+            channel(g),
+            channel(b),
+            channel(a * 255.0),
+        )),
+        _ => Err(format!("Unsupported color: {text}").into()),
+    }
+}
+
+fn read_css(text: &str) -> Result<Vec<GradientPivot>, Box<dyn Error>> {
+    let start = text
+        .find("linear-gradient(")
+        .ok_or("No linear-gradient() found")?;
+    let inner = &text[start + "linear-gradient(".len()..];
+    let end = inner.rfind(')').ok_or("Unclosed linear-gradient()")?;
+    let mut arguments = split_arguments(&inner[..end]);
+    let is_direction = |argument: &str| {
+        argument.starts_with("to ")
+            || ["deg", "grad", "rad", "turn"]
+                .iter()
+                .any(|unit| argument.ends_with(unit))
+    };
+    if arguments.first().is_some_and(|first| is_direction(first)) {
+        arguments.remove(0);
+    }
+
+    // Colors with their positions, if any
+    let mut stops: Vec<(Color, Option<f64>)> = Vec::new();
+    for argument in arguments {
+        let (color, positions) = match argument.rfind(')') {
+            Some(close) => argument.split_at(close + 1),
+            None => argument.split_at(argument.find(' ').unwrap_or(argument.len())),
+        };
+        let color = parse_css_color(color.trim())?;
+        let positions = positions
+            .split_whitespace()
+            .map(|position| {
+                position
+                    .strip_suffix('%')
+                    .ok_or_else(|| format!("Unsupported position: {position}"))?
+                    .parse::<f64>()
+                    .map(|percent| percent / 100.0)
+                    .map_err(Into::into)
+            })
+            .collect::<Result<Vec<f64>, Box<dyn Error>>>()?;
+        if positions.is_empty() {
+            stops.push((color, None));
+        }
+        for position in positions {
+            stops.push((color, Some(position)));
+        }
+    }
+
+    // Stops without positions are spread evenly between those with
+    if let Some(first) = stops.first_mut() {
+        first.1.get_or_insert(0.0);
+    }
+    if let Some(last) = stops.last_mut() {
+        last.1.get_or_insert(1.0);
+    }
+    let mut pivots = Vec::with_capacity(stops.len());
+    let mut previous = 0.0;
+    for (i, (color, position)) in stops.iter().enumerate() {
+        let value = position.unwrap_or_else(|| {
+            let (steps, next) = stops[i..]
+                .iter()
+                .enumerate()
+                .find_map(|(steps, (_, position))| position.map(|p| (steps, p)))
+                .unwrap_or((1, 1.0));
+            previous + (next - previous) / (steps + 1) as f64
+        });
+        // Positions before an earlier one are moved up to it, as in CSS
+        let value = value.max(previous).clamp(0.0, 1.0);
+        pivots.push(GradientPivot::new(value, *color));
+        previous = value;
+    }
+    Ok(pivots)
+}
+
+fn write_css(pivots: &[GradientPivot]) -> String {
+    let stops: Vec<String> = pivots
+        .iter()
+        .map(|pivot| {
+            format!(
+                "{} {}%",
+                pivot.color.as_hex_with_alpha(),
+                pivot.value * 100.0
+            )
+        })
+        .collect();
+    format!("linear-gradient(to right, {})\n", stops.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GradientFormat;
+    use crate::easing::Easing;
+    use crate::Color;
+    use crate::EngineSettings;
+
+    fn colors_and_values(pivots: &[crate::GradientPivot]) -> Vec<(String, f64, Easing)> {
+        pivots
+            .iter()
+            .map(|p| {
+                (
+                    p.color.as_hex(),
+                    (p.value * 1000.0).round() / 1000.0,
+                    p.easing,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn formats_round_trip() {
+        let settings = EngineSettings::default()
+            .gradient_insert_pivot(0)
+            .gradient_set_pivot_color(1, "#20e040")
+            .gradient_set_pivot_easing(2, Easing::Smoothstep);
+        let gradient = settings.get_gradient();
+        let expected = vec![
+            ("#000000".to_string(), 0.0, Easing::Linear),
+            ("#20e040".to_string(), 0.5, Easing::Linear),
+            ("#ff00ff".to_string(), 1.0, Easing::Smoothstep),
+        ];
+
+        let ggr = GradientFormat::Ggr.write(gradient, 50);
+        let read = GradientFormat::Ggr.read(&ggr).unwrap();
+        assert_eq!(colors_and_values(&read), expected, "{ggr}");
+
+        let css = GradientFormat::Css.write(gradient, 50);
+        assert_eq!(
+            css,
+            "linear-gradient(to right, #000000 0%, #20e040 50%, #ff00ff 100%)\n"
+        );
+        let read = GradientFormat::Css.read(&css).unwrap();
+        let linear: Vec<_> = expected
+            .iter()
+            .map(|(c, v, _)| (c.clone(), *v, Easing::Linear))
+            .collect();
+        assert_eq!(colors_and_values(&read), linear);
+
+        // Maps keep only the pivots needed to come close to every color
+        let linear = EngineSettings::default()
+            .set_iteration_limit(1000)
+            .gradient_set_relative_pivots(read);
+        let map = GradientFormat::Map.write(linear.get_gradient(), 1000);
+        assert_eq!(map.lines().count(), 256);
+        let read = GradientFormat::Map.read(&map).unwrap();
+        assert!(read.len() <= 4, "{map}");
+        assert_eq!(read.first().unwrap().color, Color::of(0, 0, 0, 255));
+        assert_eq!(read.last().unwrap().value, 1.0);
+    }
+
+    #[test]
+    fn reads_css() {
+        let read = |css| {
+            let pivots = GradientFormat::Css.read(css).unwrap();
+            colors_and_values(&pivots)
+                .into_iter()
+                .map(|(c, v, _)| (c, v))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            read(
+                "background: linear-gradient(90deg, #f00, rgb(0 128 0) 40%, rgba(0, 0, 255, 0.5));"
+            ),
+            vec![
+                ("#ff0000".to_string(), 0.0),
+                ("#008000".to_string(), 0.4),
+                ("#0000ff".to_string(), 1.0),
+            ]
+        );
+        assert_eq!(
+            read("linear-gradient(#000, #111, #222, #333 60%, #444 50%)"),
+            vec![
+                ("#000000".to_string(), 0.0),
+                ("#111111".to_string(), 0.2),
+                ("#222222".to_string(), 0.4),
+                ("#333333".to_string(), 0.6),
+                ("#444444".to_string(), 0.6),
+            ]
+        );
+        assert!(GradientFormat::Css
+            .read("radial-gradient(#000, #fff)")
+            .is_err());
+
+        let translucent = GradientFormat::Css
+            .read("linear-gradient(#ff000080, #0f08, rgba(0, 0, 255, 0.5))")
+            .unwrap();
+        let alphas: Vec<u8> = translucent.iter().map(|p| p.color.get_alpha()).collect();
+        assert_eq!(alphas, vec![128, 136, 128]);
+        let settings = EngineSettings::default().gradient_set_relative_pivots(translucent);
+        assert_eq!(
+            GradientFormat::Css.write(settings.get_gradient(), 50),
+            "linear-gradient(to right, #ff000080 0%, #00ff0088 50%, #0000ff80 100%)\n"
+        );
+    }
+
+    #[test]
+    fn reads_ggr_midpoints() {
+        let ggr = "GIMP Gradient\nName: Test\n1\n\
+            0.0 0.25 1.0 0 0 0 1 1 1 1 1 0 0\n";
+        let pivots = GradientFormat::Ggr.read(ggr).unwrap();
+        assert_eq!(
+            colors_and_values(&pivots)
+                .into_iter()
+                .map(|(c, v, _)| (c, v))
+                .collect::<Vec<_>>(),
+            vec![
+                ("#000000".to_string(), 0.0),
+                ("#808080".to_string(), 0.25),
+                ("#ffffff".to_string(), 1.0),
+            ]
+        );
+        assert!(GradientFormat::Ggr.read("GIMP Palette\n").is_err());
+        assert_eq!(
+            GradientFormat::from_file_name("Sunset.GGR"),
+            Some(GradientFormat::Ggr)
+        );
+    }
+}
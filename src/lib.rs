@@ -1,12 +1,35 @@
+//! The engine builds without any browser dependencies. The Yew user
+//! interface in [components] is behind the `web` feature, which is enabled by
+//! default.
+
+#[cfg(feature = "web")]
 include!(concat!(env!("OUT_DIR"), "/version.rs"));
 
+pub mod animation;
+pub mod boundary;
+pub mod buddhabrot;
+pub mod budget;
+pub mod color_space;
 mod complex;
+#[cfg(feature = "web")]
 pub mod components;
+#[cfg(feature = "web")]
 mod crate_info;
+pub mod easing;
+pub mod expmap;
+pub mod export;
+pub mod gradient_file;
+pub mod lighting;
+pub mod log;
 mod mandelbrot;
 pub mod math;
 pub mod presets;
 mod rect;
+pub mod scheduler;
+mod serial;
+pub mod stats;
+pub mod tiles;
+#[cfg(feature = "web")]
 mod yew;
 
 #[macro_use]
@@ -16,10 +39,29 @@ use serde::Deserialize;
 use serde::Serialize;
 use std::collections::BinaryHeap;
 use std::rc::Rc;
-use wasm_bindgen::Clamped;
-
+use std::time::Duration;
+
+use crate::boundary::BoundaryTrace;
+use crate::boundary::FillAlgorithm;
+use crate::boundary::Pixels;
+use crate::buddhabrot::Buddhabrot;
+use crate::buddhabrot::RenderMode;
+use crate::budget::Clock;
+use crate::budget::FrameBudget;
+use crate::color_space::InterpolationSpace;
 use crate::complex::Complex;
+use crate::easing::Easing;
+use crate::easing::Spline;
+use crate::expmap::ExpStrip;
+use crate::lighting::Lighting;
+use crate::mandelbrot::Average;
+use crate::mandelbrot::OrbitData;
+use crate::mandelbrot::Tracking;
 use crate::rect::RectRegion;
+use crate::scheduler::Scheduler;
+use crate::stats::RenderStats;
+use crate::tiles::Tile;
+use crate::tiles::TileGrid;
 use crate::utils::Latch;
 use crate::utils::Pristine;
 
@@ -36,10 +78,6 @@ impl Color {
         Color { r, g, b, a }
     }
 
-    fn average(&self, other: &Color) -> Color {
-        Color::lerp(self, other, 0, 2, 1)
-    }
-
     fn lerp(a: &Color, b: &Color, a_value: isize, b_value: isize, target_value: isize) -> Color {
         let dv = b_value - a_value;
 
@@ -58,44 +96,341 @@ impl Color {
         )
     }
 
-    fn parse_hex(hex: &str) -> Result<Color, std::num::ParseIntError> {
-        Ok(Color {
-            r: u8::from_str_radix(&hex[1..3], 16)?,
-            g: u8::from_str_radix(&hex[3..5], 16)?,
-            b: u8::from_str_radix(&hex[5..7], 16)?,
-            a: 255,
+    /// Parse `#rrggbb`, which is opaque, or `#rrggbbaa`.
+    fn parse_hex(hex: &str) -> Option<Color> {
+        let digits = hex
+            .strip_prefix('#')
+            .filter(|digits| digits.is_ascii() && (digits.len() == 6 || digits.len() == 8))?;
+        let channel = |i: usize| u8::from_str_radix(&digits[2 * i..2 * i + 2], 16).ok();
+        Some(Color {
+            r: channel(0)?,
+            g: channel(1)?,
+            b: channel(2)?,
+            a: if digits.len() == 8 { channel(3)? } else { 255 },
         })
     }
 
+    /// `#rrggbb`, leaving out alpha, as color inputs expect.
     pub fn as_hex(&self) -> String {
         format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
     }
+
+    /// `#rrggbbaa`, or `#rrggbb` when the color is opaque.
+    pub fn as_hex_with_alpha(&self) -> String {
+        if self.a == 255 {
+            self.as_hex()
+        } else {
+            format!("{}{:02x}", self.as_hex(), self.a)
+        }
+    }
+
+    pub fn get_alpha(&self) -> u8 {
+        self.a
+    }
+
+    pub fn as_rgba(&self) -> [u8; 4] {
+        [self.r, self.g, self.b, self.a]
+    }
+
+    /// Convert from hue in degrees, and saturation and value in `[0, 1]`.
+    fn from_hsv(h: f64, s: f64, v: f64, a: u8) -> Color {
+        let h = h.rem_euclid(360.0) / 60.0;
+        let c = v * s;
+        let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+        let (r, g, b) = match h as u8 {
+            0 => (c, x, 0.0),
+            1 => (x, c, 0.0),
+            2 => (0.0, c, x),
+            3 => (0.0, x, c),
+            4 => (x, 0.0, c),
+            _ => (c, 0.0, x),
+        };
+        let m = v - c;
+        let to_u8 = |f: f64| ((f + m) * 255.0).round() as u8;
+        Color::of(to_u8(r), to_u8(g), to_u8(b), a)
+    }
+}
+
+/// How to color points inside the set.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub enum InteriorColoring {
+    /// Paint the whole interior with the inside color.
+    #[default]
+    Flat,
+
+    /// One hue per period of the attracting cycle.
+    Period,
+
+    /// One hue per period of the attracting cycle, with brightness decreasing
+    /// with the magnitude of the cycle's multiplier.
+    PeriodMultiplier,
+}
+
+impl InteriorColoring {
+    /// Hue difference between consecutive periods: the golden angle, so that
+    /// nearby periods get clearly distinct hues.
+    const HUE_STEP: f64 = 137.507_764;
+
+    fn tracking(&self) -> Tracking {
+        Tracking {
+            cycle: *self != Self::Flat,
+            ..Default::default()
+        }
+    }
+
+    fn get_color(&self, inside: &Color, orbit: &OrbitData) -> Color {
+        if orbit.period == 0 {
+            return *inside;
+        }
+
+        let hue = f64::from(orbit.period - 1) * Self::HUE_STEP;
+        match self {
+            Self::Flat => *inside,
+            Self::Period => Color::from_hsv(hue, 0.75, 1.0, inside.a),
+            Self::PeriodMultiplier => Color::from_hsv(
+                hue,
+                0.75,
+                1.0 - 0.75 * f64::from(orbit.multiplier),
+                inside.a,
+            ),
+        }
+    }
+}
+
+/// How to color points outside the set.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub enum ExteriorColoring {
+    /// Map the escape count through the gradient.
+    #[default]
+    EscapeTime,
+
+    /// Map the average of `sin(density * arg z)` over the orbit through the
+    /// gradient.
+    StripeAverage { density: f64 },
+
+    /// Map the average turning angle of the orbit through the gradient.
+    CurvatureAverage,
+}
+
+impl ExteriorColoring {
+    fn tracking(&self) -> Tracking {
+        Tracking {
+            average: match self {
+                Self::EscapeTime => None,
+                Self::StripeAverage { density } => Some(Average::Stripe { density: *density }),
+                Self::CurvatureAverage => Some(Average::Curvature),
+            },
+            ..Default::default()
+        }
+    }
+
+    /// The palette index to use for an escaped pixel.
+    fn palette_index(&self, escape_count: usize, orbit: &OrbitData, max_value: usize) -> usize {
+        match self {
+            Self::EscapeTime => escape_count,
+            Self::StripeAverage { .. } | Self::CurvatureAverage => {
+                (f64::from(orbit.average) * max_value.saturating_sub(1) as f64).round() as usize
+            }
+        }
+    }
+}
+
+/// A function applied to escape counts before they are looked up in the
+/// palette, to spread out bands of escape counts that hold most of the detail.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub enum Transfer {
+    #[default]
+    Identity,
+
+    /// Logarithmic, stretching low escape counts and squeezing high ones.
+    Log,
+
+    /// Square root, like a milder [Transfer::Log].
+    Sqrt,
+
+    /// Escape counts as a fraction of the iteration limit, raised to
+    /// `exponent`.
+    Power { exponent: f64 },
+}
+
+impl Transfer {
+    /// The palette index for `escape_count`, which stays in `[0, max_value)`
+    /// if it was in it.
+    fn apply(&self, escape_count: usize, max_value: usize) -> usize {
+        if escape_count >= max_value {
+            return escape_count;
+        }
+        let x = escape_count as f64;
+        let max = max_value as f64;
+        let fraction = match self {
+            Self::Identity => return escape_count,
+            Self::Log => x.ln_1p() / max.ln_1p(),
+            Self::Sqrt => (x / max).sqrt(),
+            Self::Power { exponent } => (x / max).powf(*exponent),
+        };
+        ((fraction * max) as usize).min(max_value - 1)
+    }
+}
+
+/// A modulation of exterior colors by the argument of the final `z` of each
+/// escaping orbit.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub enum Decomposition {
+    #[default]
+    Off,
+
+    /// Black where the final `z` is in the lower half-plane.
+    Binary,
+
+    /// Darken by which of `sectors` equal angular sectors the final `z` is in,
+    /// which traces the external field lines.
+    Nary { sectors: u32 },
+}
+
+impl Decomposition {
+    /// How much to darken sectors past the first in n-ary decomposition.
+    const SECTOR_SHADE: f64 = 0.75;
+
+    fn tracking(&self) -> Tracking {
+        Tracking {
+            final_z: *self != Self::Off,
+            ..Default::default()
+        }
+    }
+
+    fn modulate(&self, color: &Color, orbit: &OrbitData) -> Color {
+        let (re, im) = orbit.final_z;
+        let factor = match self {
+            Self::Off => return *color,
+            Self::Binary if im < 0.0 => 0.0,
+            Self::Binary => return *color,
+            Self::Nary { sectors } => {
+                let sectors = (*sectors).max(1);
+                let turns = f64::from(im)
+                    .atan2(f64::from(re))
+                    .rem_euclid(std::f64::consts::TAU)
+                    / std::f64::consts::TAU;
+                let sector = ((turns * f64::from(sectors)) as u32).min(sectors - 1);
+                1.0 - Self::SECTOR_SHADE * f64::from(sector) / f64::from(sectors)
+            }
+        };
+        let scale = |channel: u8| (f64::from(channel) * factor).round() as u8;
+        Color::of(scale(color.r), scale(color.g), scale(color.b), color.a)
+    }
+}
+
+/// What the values of [GradientPivot]s are measured in.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub enum PivotScale {
+    /// Escape counts.
+    #[default]
+    Absolute,
+
+    /// Fractions of the iteration limit, so the gradient stretches with it.
+    IterationLimit,
+
+    /// Fractions of the range from the lowest to the highest escape count in
+    /// the image.
+    EscapeRange,
+}
+
+/// How the gradient carries on past its last pivot.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub enum Spread {
+    /// Keep the color of the last pivot.
+    #[default]
+    Pad,
+
+    /// Stretch the pivots over `period` escape counts, and start over from the
+    /// first pivot after each period. Colors are shifted back by `offset`
+    /// escape counts.
+    Repeat { period: usize, offset: usize },
+
+    /// Like [Spread::Repeat], but run back from the last pivot to the first in
+    /// every other period.
+    Mirror { period: usize, offset: usize },
+}
+
+impl Spread {
+    /// The number of escape counts the pivots are stretched over, if any.
+    fn period(&self) -> Option<usize> {
+        match self {
+            Self::Pad => None,
+            Self::Repeat { period, .. } | Self::Mirror { period, .. } => Some((*period).max(1)),
+        }
+    }
+
+    /// The number of escape counts after which the colors repeat, if they do.
+    fn cycle_length(&self) -> Option<usize> {
+        match self {
+            Self::Pad => None,
+            Self::Repeat { .. } => self.period(),
+            Self::Mirror { .. } => self.period().map(|period| 2 * period),
+        }
+    }
+
+    /// The index into a single period of colors for `index`.
+    fn index(&self, index: usize) -> usize {
+        match *self {
+            Self::Pad => index,
+            Self::Repeat { offset, .. } | Self::Mirror { offset, .. } => {
+                let period = self.period().unwrap_or(1);
+                let index = (index + offset) % self.cycle_length().unwrap_or(1);
+                if index <= period {
+                    index
+                } else {
+                    2 * period - index
+                }
+            }
+        }
+    }
 }
 
-#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct GradientPivot {
-    pub value: usize,
+    /// Where the pivot sits, as measured by the gradient's [PivotScale].
+    pub value: f64,
     pub color: Color,
+    /// How colors change along the segment leading up to this pivot.
+    pub easing: Easing,
 }
 
 impl GradientPivot {
-    fn new(value: usize, color: Color) -> Self {
-        GradientPivot { value, color }
+    fn new(value: f64, color: Color) -> Self {
+        GradientPivot {
+            value,
+            color,
+            easing: Easing::Linear,
+        }
     }
 
-    fn average(&self, other: &Self) -> Self {
+    /// A pivot halfway along the segment from this pivot to `other`.
+    fn average(&self, other: &Self, space: InterpolationSpace) -> Self {
+        let color = if other.easing == Easing::Linear {
+            space.interpolate(&self.color, &other.color, 0, 2, 1)
+        } else {
+            space.interpolate_at(&self.color, &other.color, other.easing.apply(0.5))
+        };
         GradientPivot {
-            value: (self.value + other.value) / 2,
-            color: self.color.average(&other.color),
+            value: (self.value + other.value) / 2.0,
+            color,
+            easing: other.easing,
         }
     }
 }
 
-#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Gradient {
     inside: Color,
     root: Color,
     pivots: Vec<GradientPivot>,
+    interior: InteriorColoring,
+    decomposition: Decomposition,
+    interpolation: InterpolationSpace,
+    pivot_scale: PivotScale,
+    spread: Spread,
+    /// A smooth curve through all the pivots, overriding their easings.
+    spline: Option<Spline>,
 }
 
 impl Default for Gradient {
@@ -104,14 +439,24 @@ impl Default for Gradient {
             inside: Color::of(0, 0, 0, 255),
             root: Color::of(0, 0, 0, 255),
             pivots: vec![
-                GradientPivot::new(0, Color::of(0, 0, 0, 255)),
-                GradientPivot::new(50, Color::of(255, 0, 255, 255)),
+                GradientPivot::new(0.0, Color::of(0, 0, 0, 255)),
+                GradientPivot::new(50.0, Color::of(255, 0, 255, 255)),
             ],
+            interior: InteriorColoring::Flat,
+            decomposition: Decomposition::Off,
+            interpolation: InterpolationSpace::Srgb,
+            pivot_scale: PivotScale::Absolute,
+            spread: Spread::Pad,
+            spline: None,
         }
     }
 }
 
 impl Gradient {
+    /// How far dragging a pivot pushes its neighbours out of the way when
+    /// pivots are relative.
+    const RELATIVE_PIVOT_STEP: f64 = 0.001;
+
     pub fn get_pivots(&self) -> &[GradientPivot] {
         &self.pivots
     }
@@ -124,10 +469,123 @@ impl Gradient {
         self.inside = color;
     }
 
+    pub fn get_interior_coloring(&self) -> InteriorColoring {
+        self.interior
+    }
+
+    fn set_interior_coloring(&mut self, interior: InteriorColoring) {
+        self.interior = interior;
+    }
+
+    pub fn get_decomposition(&self) -> Decomposition {
+        self.decomposition
+    }
+
+    fn set_decomposition(&mut self, decomposition: Decomposition) {
+        self.decomposition = decomposition;
+    }
+
+    pub fn get_interpolation(&self) -> InterpolationSpace {
+        self.interpolation
+    }
+
+    fn set_interpolation(&mut self, interpolation: InterpolationSpace) {
+        self.interpolation = interpolation;
+    }
+
+    pub fn get_pivot_scale(&self) -> PivotScale {
+        self.pivot_scale
+    }
+
+    /// Measure pivots in `pivot_scale` instead, keeping them where they are
+    /// for `iteration_limit`.
+    fn set_pivot_scale(&mut self, pivot_scale: PivotScale, iteration_limit: usize) {
+        let limit = iteration_limit as f64;
+        for pivot in &mut self.pivots {
+            match (self.pivot_scale, pivot_scale) {
+                (PivotScale::Absolute, PivotScale::Absolute) => {}
+                (PivotScale::Absolute, _) => pivot.value /= limit.max(1.0),
+                (_, PivotScale::Absolute) => pivot.value = (pivot.value * limit).round(),
+                _ => {}
+            }
+        }
+        self.pivot_scale = pivot_scale;
+    }
+
+    pub fn get_spread(&self) -> Spread {
+        self.spread
+    }
+
+    fn set_spread(&mut self, spread: Spread) {
+        self.spread = spread;
+    }
+
+    pub fn get_spline(&self) -> Option<Spline> {
+        self.spline
+    }
+
+    fn set_spline(&mut self, spline: Option<Spline>) {
+        self.spline = spline;
+    }
+
+    fn set_pivot_easing(&mut self, index: usize, easing: Easing) -> bool {
+        if let Some(pivot) = self.pivots.get_mut(index) {
+            pivot.easing = easing;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// The escape counts of the pivots, given the iteration limit.
+    fn escape_counts(&self, iteration_limit: usize) -> Vec<usize> {
+        let scale = match self.pivot_scale {
+            PivotScale::Absolute => 1.0,
+            PivotScale::IterationLimit | PivotScale::EscapeRange => iteration_limit as f64,
+        };
+        self.pivots
+            .iter()
+            .map(|pivot| (pivot.value * scale).round() as usize)
+            .collect()
+    }
+
+    /// The escape count of the last pivot, given the iteration limit.
+    pub fn last_escape_count(&self, iteration_limit: usize) -> usize {
+        self.escape_counts(iteration_limit)
+            .last()
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// The largest pivot value, given the iteration limit.
+    pub fn max_pivot_value(&self, iteration_limit: usize) -> f64 {
+        if self.pivot_scale == PivotScale::Absolute {
+            iteration_limit as f64
+        } else {
+            1.0
+        }
+    }
+
+    /// Where `pivot` sits along the gradient, as a fraction in `[0, 1]`.
+    pub fn pivot_fraction(&self, pivot: &GradientPivot, iteration_limit: usize) -> f64 {
+        pivot.value / self.max_pivot_value(iteration_limit)
+    }
+
+    fn tracking(&self) -> Tracking {
+        Tracking {
+            cycle: self.interior.tracking().cycle,
+            final_z: self.decomposition.tracking().final_z,
+            ..Default::default()
+        }
+    }
+
     fn insert_pivot(&mut self, index: usize) -> GradientPivot {
         if let Some(pivot_after) = self.pivots.get(index + 1) {
             let pivot_before = &self.pivots[index];
-            let pivot = pivot_before.average(pivot_after);
+            let mut pivot = pivot_before.average(pivot_after, self.interpolation);
+            if self.pivot_scale == PivotScale::Absolute {
+                pivot.value = pivot.value.floor();
+            }
             self.pivots.insert(index + 1, pivot.clone());
             pivot
         } else {
@@ -137,22 +595,52 @@ impl Gradient {
         }
     }
 
+    /// Replace the pivots with `pivots`, whose values are fractions of the way
+    /// along the gradient, starting from the color of the first.
+    fn set_relative_pivots(&mut self, pivots: Vec<GradientPivot>, iteration_limit: usize) {
+        let max_value = self.max_pivot_value(iteration_limit);
+        let absolute = self.pivot_scale == PivotScale::Absolute;
+        if let Some(first) = pivots.first() {
+            self.root = first.color;
+        }
+        self.pivots = pivots
+            .into_iter()
+            .map(|pivot| {
+                let value = pivot.value.clamp(0.0, 1.0) * max_value;
+                GradientPivot {
+                    value: if absolute { value.round() } else { value },
+                    ..pivot
+                }
+            })
+            .collect();
+    }
+
     fn delete_pivot(&mut self, index: usize) {
         self.pivots.remove(index);
     }
 
-    fn set_pivot_value(&mut self, index: usize, value: usize, max_value: usize) -> Option<usize> {
-        let new_value = std::cmp::max(std::cmp::min(value, max_value), 0);
+    fn set_pivot_value(&mut self, index: usize, value: f64, max_value: f64) -> Option<f64> {
+        let step = if self.pivot_scale == PivotScale::Absolute {
+            1.0
+        } else {
+            Self::RELATIVE_PIVOT_STEP
+        };
+        let value = if self.pivot_scale == PivotScale::Absolute {
+            value.round()
+        } else {
+            value
+        };
+        let new_value = value.clamp(0.0, max_value);
 
         if let Some(i) = index.checked_sub(1) {
-            if new_value > 0 && new_value <= self.pivots[i].value {
-                self.set_pivot_value(i, new_value.saturating_sub(1), max_value);
+            if new_value > 0.0 && new_value <= self.pivots[i].value {
+                self.set_pivot_value(i, (new_value - step).max(0.0), max_value);
             }
         }
 
         if let Some(i) = index.checked_add(1).filter(|i| *i < self.pivots.len()) {
             if new_value >= self.pivots[i].value {
-                self.set_pivot_value(i, new_value + 1, max_value);
+                self.set_pivot_value(i, new_value + step, max_value);
             }
         }
 
@@ -172,38 +660,111 @@ impl Gradient {
     }
 
     fn make_palette(&self, max_value: usize) -> Palette {
-        let mut values: Vec<Color> = Vec::with_capacity(self.pivots.last().unwrap().value + 1);
+        let mut escape_counts = self.escape_counts(max_value);
+        if let Some(period) = self.spread.period() {
+            let span = escape_counts.last().copied().unwrap_or_default().max(1);
+            for escape_count in &mut escape_counts {
+                *escape_count = *escape_count * period / span;
+            }
+        }
+
+        let mut values: Vec<Color> = Vec::with_capacity(escape_counts.last().unwrap() + 1);
         values.push(self.root);
-        let mut prev_i = 0;
-        let mut prev_color = &self.root;
+        if let Some(spline) = self.spline {
+            values.extend(self.spline_colors(spline, &escape_counts));
+        } else {
+            let mut prev_i = 0;
+            let mut prev_color = &self.root;
+
+            for (escape_count, GradientPivot { color, easing, .. }) in
+                escape_counts.iter().zip(&self.pivots)
+            {
+                for i in prev_i..*escape_count {
+                    values.push(if *easing == Easing::Linear {
+                        self.interpolation.interpolate(
+                            prev_color,
+                            color,
+                            prev_i as isize,
+                            *escape_count as isize,
+                            i as isize,
+                        )
+                    } else {
+                        let t = (i - prev_i) as f64 / (escape_count - prev_i) as f64;
+                        self.interpolation
+                            .interpolate_at(prev_color, color, easing.apply(t))
+                    });
+                }
 
-        for GradientPivot {
-            value: escape_count,
-            color,
-        } in &self.pivots
-        {
-            for i in prev_i..*escape_count {
-                values.push(Color::lerp(
-                    prev_color,
-                    color,
-                    prev_i as isize,
-                    *escape_count as isize,
-                    i as isize,
-                ));
+                prev_i = *escape_count;
+                prev_color = color;
             }
-
-            prev_i = *escape_count;
-            prev_color = color;
         }
 
-        while values.len() <= max_value {
+        let len = self.spread.period().unwrap_or(max_value);
+        while values.len() <= len {
             values.push(self.pivots.last().unwrap().color);
         }
 
         Palette {
             escape_values: values,
             inside_color: self.inside,
+            interior: self.interior,
+            decomposition: self.decomposition,
+            phase: 0,
+            pivot_scale: self.pivot_scale,
+            escape_range: (0, max_value),
+            spread: self.spread,
+        }
+    }
+
+    /// Colors for escape counts up to the last of `escape_counts`, along
+    /// `spline` through the root color and each pivot.
+    fn spline_colors(&self, spline: Spline, escape_counts: &[usize]) -> Vec<Color> {
+        let mut knots: Vec<(f64, Color)> = vec![(0.0, self.root)];
+        for (escape_count, pivot) in escape_counts.iter().zip(&self.pivots) {
+            let x = *escape_count as f64;
+            if knots.last().is_some_and(|(last, _)| *last >= x) {
+                knots.pop();
+            }
+            knots.push((x, pivot.color));
         }
+
+        let xs: Vec<f64> = knots.iter().map(|(x, _)| *x).collect();
+        let colors: Vec<Color> = knots.iter().map(|(_, color)| *color).collect();
+        let channels = self.interpolation.encode_sequence(&colors);
+        let targets: Vec<f64> = (0..escape_counts.last().copied().unwrap_or_default())
+            .map(|i| i as f64)
+            .collect();
+        let sampled = [0, 1, 2, 3].map(|channel| {
+            let ys: Vec<f64> = channels.iter().map(|c| c[channel]).collect();
+            spline.sample(&xs, &ys, &targets)
+        });
+        (0..targets.len())
+            .map(|i| {
+                self.interpolation
+                    .decode_color(sampled.each_ref().map(|channel| channel[i]))
+            })
+            .collect()
+    }
+
+    /// The colors of `width` escape counts spread evenly from zero to the
+    /// iteration limit, as the palette colors them after `transfer`.
+    pub fn ribbon_colors(
+        &self,
+        iteration_limit: usize,
+        transfer: &Transfer,
+        width: usize,
+    ) -> Vec<Color> {
+        let palette = self.make_palette(iteration_limit);
+        (0..width)
+            .map(|x| {
+                let escape_count = x * iteration_limit / width.max(1);
+                *palette.get_color(
+                    transfer.apply(escape_count, iteration_limit),
+                    iteration_limit,
+                )
+            })
+            .collect()
     }
 }
 
@@ -211,18 +772,57 @@ impl Gradient {
 pub struct Palette {
     escape_values: Vec<Color>,
     inside_color: Color,
+    interior: InteriorColoring,
+    decomposition: Decomposition,
+    /// Escape counts are colored as if they were this much greater, wrapping
+    /// around at the iteration limit or the end of the spread's cycle, so that
+    /// stepping it cycles the colors.
+    phase: usize,
+    pivot_scale: PivotScale,
+    /// The lowest and highest escape counts outside the set, which
+    /// [PivotScale::EscapeRange] stretches the gradient over. Counts outside
+    /// it, from a range measured on another image, take the nearest end.
+    escape_range: (usize, usize),
+    /// Colors past `escape_values` follow from this, rather than padding.
+    spread: Spread,
 }
 
 impl Palette {
+    /// The palette index for `escape_count`, stretched over the escape range
+    /// if the pivots are relative to it.
+    fn escape_index(&self, escape_count: usize, max_value: usize) -> usize {
+        let (low, high) = self.escape_range;
+        if self.pivot_scale != PivotScale::EscapeRange || escape_count >= max_value {
+            escape_count
+        } else if high <= low {
+            0
+        } else {
+            let index =
+                (escape_count.saturating_sub(low) * max_value.saturating_sub(1)) / (high - low);
+            std::cmp::min(index, max_value.saturating_sub(1))
+        }
+    }
+
     fn get_color(&self, escape_count: usize, max_value: usize) -> &Color {
         if escape_count >= max_value {
             &self.inside_color
         } else {
-            self.escape_values
-                .get(escape_count)
-                .unwrap_or(&self.inside_color)
+            let index = if self.spread == Spread::Pad {
+                (escape_count + self.phase) % max_value
+            } else {
+                self.spread.index(escape_count + self.phase)
+            };
+            self.escape_values.get(index).unwrap_or(&self.inside_color)
         }
     }
+
+    fn get_interior_color(&self, orbit: &OrbitData) -> Color {
+        self.interior.get_color(&self.inside_color, orbit)
+    }
+
+    fn modulate_exterior_color(&self, color: &Color, orbit: &OrbitData) -> Color {
+        self.decomposition.modulate(color, orbit)
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -231,6 +831,11 @@ pub struct Image {
     height: usize,
     palette: Palette,
     escape_counts: Vec<usize>,
+    /// Extra per-pixel values, or empty if no extra values are tracked.
+    orbits: Vec<OrbitData>,
+    /// Whether each escape count was computed for the current view, rather
+    /// than filled in or left over from another view.
+    computed: Vec<bool>,
     pixels: Vec<u8>,
 }
 
@@ -241,52 +846,120 @@ impl Image {
             height,
             palette,
             escape_counts: vec![0; width * height],
+            orbits: Vec::new(),
+            computed: vec![false; width * height],
             pixels: vec![0; width * height * 4],
         }
     }
 
+    fn set_tracking(&mut self, tracking: &Tracking) {
+        if tracking.any() {
+            self.orbits = vec![OrbitData::default(); self.width * self.height];
+        } else {
+            self.orbits = Vec::new();
+        }
+    }
+
+    /// The index of the pixel mirrored from `(x, y)` by `mirror`, if it is in
+    /// the image and is not the same pixel.
+    fn mirrored_index(&self, mirror: Option<i32>, x: i32, y: i32) -> Option<usize> {
+        let mirrored_y = mirror? - y;
+        (mirrored_y != y && mirrored_y >= 0 && mirrored_y < self.height as i32)
+            .then(|| x as usize + mirrored_y as usize * self.width)
+    }
+
+    /// Stop copying pixels that didn't escape, which could with a lower
+    /// `iteration_limit` have escaped or ended with other orbit data.
+    fn forget_interior(&mut self, iteration_limit: usize) {
+        for (computed, escape_count) in self.computed.iter_mut().zip(&self.escape_counts) {
+            *computed &= *escape_count < iteration_limit;
+        }
+    }
+
     pub fn pan(&mut self, dx: i32, dy: i32) {
-        let di: usize =
-            (dx + (dy * self.width as i32)).rem_euclid(self.escape_counts.len() as i32) as usize;
+        Self::pan_values(&mut self.escape_counts, self.width, self.height, dx, dy);
+        if !self.orbits.is_empty() {
+            Self::pan_values(&mut self.orbits, self.width, self.height, dx, dy);
+        }
+        Self::pan_values(&mut self.computed, self.width, self.height, dx, dy);
+    }
 
-        let v: Vec<usize> = self.escape_counts.clone();
-        let l = self.escape_counts.len();
+    fn pan_values<T>(values: &mut [T], width: usize, height: usize, dx: i32, dy: i32)
+    where
+        T: Clone,
+        T: Default,
+    {
+        let di: usize = (dx + (dy * width as i32)).rem_euclid(values.len() as i32) as usize;
+
+        let v: Vec<T> = values.to_vec();
+        let l = values.len();
         for (i, v) in v.into_iter().enumerate() {
-            self.escape_counts[(i + di) % l] = v;
+            values[(i + di) % l] = v;
         }
 
         let y_to_zero = if dy >= 0 {
             0..(dy as usize)
         } else {
-            (self.height - ((-dy) as usize))..self.height
+            (height - ((-dy) as usize))..height
         };
 
         let x_to_zero = if dx >= 0 {
             0..(dx as usize)
         } else {
-            (self.width - ((-dx) as usize))..self.width
+            (width - ((-dx) as usize))..width
         };
 
         for y in y_to_zero {
-            let yw = y * self.width;
-            for x in 0..self.width {
-                let i = (x + yw).rem_euclid(self.escape_counts.len());
-                self.escape_counts[i] = 0;
+            let yw = y * width;
+            for x in 0..width {
+                let i = (x + yw).rem_euclid(values.len());
+                values[i] = T::default();
             }
         }
-        for y in 0..self.height {
-            let yw = y * self.width;
+        for y in 0..height {
+            let yw = y * width;
             for x in x_to_zero.clone() {
-                let i = (x + yw).rem_euclid(self.escape_counts.len());
-                self.escape_counts[i] = 0;
+                let i = (x + yw).rem_euclid(values.len());
+                values[i] = T::default();
             }
         }
     }
 
-    pub fn render_pixels(&mut self, max_value: usize) {
+    pub fn render_pixels(
+        &mut self,
+        max_value: usize,
+        exterior: &ExteriorColoring,
+        transfer: &Transfer,
+        lighting: Option<&Lighting>,
+    ) {
         for i in 0..self.escape_counts.len() {
             let pixel_index = i * 4;
-            let color = self.palette.get_color(self.escape_counts[i], max_value);
+            let modified_color;
+            let escape_index = transfer.apply(
+                self.palette.escape_index(self.escape_counts[i], max_value),
+                max_value,
+            );
+            let color = if self.orbits.is_empty() {
+                self.palette.get_color(escape_index, max_value)
+            } else if self.escape_counts[i] >= max_value {
+                modified_color = self.palette.get_interior_color(&self.orbits[i]);
+                &modified_color
+            } else {
+                let orbit = &self.orbits[i];
+                let color = self.palette.modulate_exterior_color(
+                    self.palette.get_color(
+                        exterior.palette_index(escape_index, orbit, max_value),
+                        max_value,
+                    ),
+                    orbit,
+                );
+                modified_color = if let Some(lighting) = lighting {
+                    lighting.shade(&color, orbit.normal)
+                } else {
+                    color
+                };
+                &modified_color
+            };
             self.pixels[pixel_index] = color.r;
             self.pixels[pixel_index + 1] = color.g;
             self.pixels[pixel_index + 2] = color.b;
@@ -296,8 +969,8 @@ impl Image {
 }
 
 impl Image {
-    fn image_data(&self) -> Clamped<&[u8]> {
-        Clamped(self.pixels.as_slice())
+    fn image_data(&self) -> &[u8] {
+        self.pixels.as_slice()
     }
 }
 
@@ -312,27 +985,40 @@ pub struct Viewpoint {
     pub scale: f64,
 }
 
-#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct EngineSettings {
-    #[serde(skip)]
     size: Latch<(usize, usize)>,
     center: Latch<Complex<f64>>,
     scale: Latch<f64>,
     iteration_limit: Latch<usize>,
     gradient: Pristine<Rc<Gradient>>,
-    #[serde(skip)]
     zoom_focus: Latch<Option<(usize, usize)>>,
+    render_mode: Latch<RenderMode>,
+    lighting: Latch<Option<Lighting>>,
+    exterior_coloring: Latch<ExteriorColoring>,
+    palette_cycle: Latch<Option<f64>>,
+    transfer: Latch<Transfer>,
+    fill_algorithm: Latch<FillAlgorithm>,
+    uniform_fill_guard: Latch<Option<usize>>,
+    scheduler: Latch<Scheduler>,
+    cursor: Latch<Option<(usize, usize)>>,
 }
 
 impl EngineSettings {
     const SERIAL_VERSION_PREFIX: &'static str = "0:";
+    /// The same format, extended with settings added since.
+    const EXTENDED_SERIAL_VERSION_PREFIX: &'static str = "1:";
+
+    /// A guard size for [Self::set_uniform_fill_guard] that rarely misses
+    /// visible features.
+    pub const DEFAULT_UNIFORM_FILL_GUARD: usize = 16;
 
     fn base64_config() -> base64::Config {
         base64::Config::new(base64::CharacterSet::UrlSafe, false)
     }
 
     fn try_serialize(&self) -> Result<String, bincode::Error> {
-        let bin = bincode::serialize(self)?;
+        let (bin, extended) = serial::encode(self)?;
 
         use std::io::Write;
         let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::best());
@@ -341,13 +1027,25 @@ impl EngineSettings {
 
         Ok(format!(
             "{}{}",
-            Self::SERIAL_VERSION_PREFIX,
+            if extended {
+                Self::EXTENDED_SERIAL_VERSION_PREFIX
+            } else {
+                Self::SERIAL_VERSION_PREFIX
+            },
             base64::encode_config(zip, Self::base64_config())
         ))
     }
 
-    fn try_restore(serialized: &str) -> Result<Self, Box<dyn std::error::Error>> {
-        if let Some(unprefixed) = serialized.strip_prefix(Self::SERIAL_VERSION_PREFIX) {
+    pub fn try_restore(serialized: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let (unprefixed, extended) = if let Some(unprefixed) =
+            serialized.strip_prefix(Self::EXTENDED_SERIAL_VERSION_PREFIX)
+        {
+            (Some(unprefixed), true)
+        } else {
+            (serialized.strip_prefix(Self::SERIAL_VERSION_PREFIX), false)
+        };
+
+        if let Some(unprefixed) = unprefixed {
             let zip = base64::decode_config(unprefixed, Self::base64_config())?;
 
             use std::io::Read;
@@ -355,8 +1053,7 @@ impl EngineSettings {
             let mut bin = Vec::new();
             decoder.read_to_end(&mut bin)?;
 
-            let deserialized: EngineSettings = bincode::deserialize(&bin)?;
-            Ok(deserialized)
+            Ok(serial::decode(&bin, extended)?)
         } else {
             Err("Unsupported state version".into())
         }
@@ -391,6 +1088,17 @@ impl EngineSettings {
         self.size.current().1
     }
 
+    /// The point at the center of the view, as `(re, im)`.
+    pub fn get_center(&self) -> (f64, f64) {
+        let center = self.center.current();
+        (center.re, center.im)
+    }
+
+    /// The distance between adjacent pixels in the complex plane.
+    pub fn get_scale(&self) -> f64 {
+        *self.scale.current()
+    }
+
     pub fn get_iteration_limit(&self) -> usize {
         *self.iteration_limit.current()
     }
@@ -399,6 +1107,43 @@ impl EngineSettings {
         self.gradient.get()
     }
 
+    pub fn get_fill_algorithm(&self) -> &FillAlgorithm {
+        self.fill_algorithm.current()
+    }
+
+    /// The smallest width and height of a region that is filled in when its
+    /// whole border has the same escape count, or `None` if only regions
+    /// inside the set are filled in.
+    pub fn get_uniform_fill_guard(&self) -> Option<usize> {
+        *self.uniform_fill_guard.current()
+    }
+
+    pub fn get_scheduler(&self) -> &Scheduler {
+        self.scheduler.current()
+    }
+
+    pub fn get_render_mode(&self) -> &RenderMode {
+        self.render_mode.current()
+    }
+
+    pub fn get_lighting(&self) -> Option<&Lighting> {
+        self.lighting.current().as_ref()
+    }
+
+    pub fn get_exterior_coloring(&self) -> &ExteriorColoring {
+        self.exterior_coloring.current()
+    }
+
+    pub fn get_transfer(&self) -> &Transfer {
+        self.transfer.current()
+    }
+
+    /// How fast colors cycle, in steps along the gradient per second, or
+    /// `None` if they don't.
+    pub fn get_palette_cycle(&self) -> Option<f64> {
+        *self.palette_cycle.current()
+    }
+
     pub fn serialize(&self) -> Option<String> {
         self.try_serialize().ok()
     }
@@ -412,6 +1157,15 @@ impl EngineSettings {
         self
     }
 
+    /// Replace the center and scale outright, rather than panning or zooming
+    /// to them. Only for settings that no [Engine] has applied yet, since an
+    /// engine doesn't notice the change.
+    pub fn set_view(mut self, (re, im): (f64, f64), scale: f64) -> Self {
+        self.center = Latch::new(Complex::from((re, im)));
+        self.scale = Latch::new(scale);
+        self
+    }
+
     pub fn pan(mut self, dx: i32, dy: i32) -> Self {
         let scale = *self.scale.current();
         self.center
@@ -442,24 +1196,77 @@ impl EngineSettings {
     }
 
     pub fn set_iteration_limit(mut self, iteration_limit: usize) -> Self {
-        if let Some(pivot) = Rc::make_mut(&mut self.gradient).pivots.last_mut() {
-            pivot.value = iteration_limit;
+        if self.gradient.get_pivot_scale() == PivotScale::Absolute {
+            if let Some(pivot) = Rc::make_mut(&mut self.gradient).pivots.last_mut() {
+                pivot.value = iteration_limit as f64;
+            }
         }
         self.iteration_limit.set(iteration_limit);
         self
     }
 
-    pub fn gradient_set_pivot_value(mut self, index: usize, value: usize) -> Self {
-        Rc::make_mut(&mut self.gradient).set_pivot_value(
-            index,
-            value,
-            *self.iteration_limit.current(),
-        );
+    pub fn set_fill_algorithm(mut self, fill_algorithm: FillAlgorithm) -> Self {
+        self.fill_algorithm.set(fill_algorithm);
+        self
+    }
+
+    /// Opt in to filling regions of the exterior whose whole border has the
+    /// same escape count. This is faster, but can miss features smaller than
+    /// the region, so regions narrower than `guard` are always subdivided,
+    /// or with [FillAlgorithm::BoundaryTrace], computed.
+    pub fn set_uniform_fill_guard(mut self, guard: Option<usize>) -> Self {
+        self.uniform_fill_guard.set(guard);
+        self
+    }
+
+    pub fn set_scheduler(mut self, scheduler: Scheduler) -> Self {
+        self.scheduler.set(scheduler);
+        self
+    }
+
+    /// Move the point that [Scheduler::CursorFollow] computes first.
+    pub fn set_cursor(mut self, x: usize, y: usize) -> Self {
+        self.cursor.set(Some((x, y)));
+        self
+    }
+
+    pub fn set_render_mode(mut self, render_mode: RenderMode) -> Self {
+        self.render_mode.set(render_mode);
+        self
+    }
+
+    pub fn set_lighting(mut self, lighting: Option<Lighting>) -> Self {
+        self.lighting.set(lighting);
+        self
+    }
+
+    pub fn set_exterior_coloring(mut self, exterior_coloring: ExteriorColoring) -> Self {
+        self.exterior_coloring.set(exterior_coloring);
+        self
+    }
+
+    pub fn set_transfer(mut self, transfer: Transfer) -> Self {
+        self.transfer.set(transfer);
+        self
+    }
+
+    /// Cycle colors at `speed` steps along the gradient per second. Only the
+    /// view advances the cycle, with [Engine::set_palette_phase].
+    pub fn set_palette_cycle(mut self, speed: Option<f64>) -> Self {
+        self.palette_cycle.set(speed);
+        self
+    }
+
+    pub fn gradient_set_pivot_value(mut self, index: usize, value: f64) -> Self {
+        let max_value = self
+            .gradient
+            .max_pivot_value(*self.iteration_limit.latest());
+        Rc::make_mut(&mut self.gradient).set_pivot_value(index, value, max_value);
         self
     }
 
     pub fn gradient_set_pivot_color(mut self, index: usize, color: &str) -> Self {
-        if let Ok(color) = Color::parse_hex(color) {
+        if let Some(color) = Color::parse_hex(color) {
             Rc::make_mut(&mut self.gradient).set_pivot_color(index, color);
         }
         self
@@ -475,9 +1282,79 @@ impl EngineSettings {
         self
     }
 
-    pub fn gradient_set_inside_color(mut self, color: &str) -> Self {
-        if let Ok(color) = Color::parse_hex(color) {
-            Rc::make_mut(&mut self.gradient).set_inside_color(color);
+    pub fn gradient_set_interior_coloring(mut self, interior: InteriorColoring) -> Self {
+        Rc::make_mut(&mut self.gradient).set_interior_coloring(interior);
+        self
+    }
+
+    pub fn gradient_set_decomposition(mut self, decomposition: Decomposition) -> Self {
+        Rc::make_mut(&mut self.gradient).set_decomposition(decomposition);
+        self
+    }
+
+    pub fn gradient_set_interpolation(mut self, interpolation: InterpolationSpace) -> Self {
+        Rc::make_mut(&mut self.gradient).set_interpolation(interpolation);
+        self
+    }
+
+    /// Measure pivots in `pivot_scale`, converting them so the gradient looks
+    /// the same at the current iteration limit.
+    pub fn gradient_set_pivot_scale(mut self, pivot_scale: PivotScale) -> Self {
+        Rc::make_mut(&mut self.gradient)
+            .set_pivot_scale(pivot_scale, *self.iteration_limit.latest());
+        self
+    }
+
+    pub fn gradient_set_spread(mut self, spread: Spread) -> Self {
+        Rc::make_mut(&mut self.gradient).set_spread(spread);
+        self
+    }
+
+    pub fn gradient_set_pivot_easing(mut self, index: usize, easing: Easing) -> Self {
+        Rc::make_mut(&mut self.gradient).set_pivot_easing(index, easing);
+        self
+    }
+
+    pub fn gradient_set_spline(mut self, spline: Option<Spline>) -> Self {
+        Rc::make_mut(&mut self.gradient).set_spline(spline);
+        self
+    }
+
+    /// Color like `other`, keeping the view. Only what differs is changed, so
+    /// that an engine only recomputes what the new coloring needs.
+    pub fn set_coloring_of(mut self, other: &EngineSettings) -> Self {
+        if self.gradient.as_ref() != other.gradient.as_ref() {
+            *self.gradient = Rc::clone(&other.gradient);
+        }
+        if self.render_mode.latest() != other.render_mode.latest() {
+            self.render_mode.set(*other.render_mode.latest());
+        }
+        if self.lighting.latest() != other.lighting.latest() {
+            self.lighting.set(*other.lighting.latest());
+        }
+        if self.exterior_coloring.latest() != other.exterior_coloring.latest() {
+            self.exterior_coloring
+                .set(*other.exterior_coloring.latest());
+        }
+        if self.transfer.latest() != other.transfer.latest() {
+            self.transfer.set(*other.transfer.latest());
+        }
+        self
+    }
+
+    /// Replace the pivots with those read from a file, such as with
+    /// [gradient_file::GradientFormat::read].
+    pub fn gradient_set_relative_pivots(mut self, pivots: Vec<GradientPivot>) -> Self {
+        if !pivots.is_empty() {
+            Rc::make_mut(&mut self.gradient)
+                .set_relative_pivots(pivots, *self.iteration_limit.latest());
+        }
+        self
+    }
+
+    pub fn gradient_set_inside_color(mut self, color: &str) -> Self {
+        if let Some(color) = Color::parse_hex(color) {
+            Rc::make_mut(&mut self.gradient).set_inside_color(color);
         }
         self
     }
@@ -502,54 +1379,173 @@ impl Default for EngineSettings {
             iteration_limit: 50.into(),
             gradient: Default::default(),
             zoom_focus: None.into(),
+            render_mode: Default::default(),
+            lighting: Default::default(),
+            exterior_coloring: Default::default(),
+            palette_cycle: Default::default(),
+            transfer: Default::default(),
+            fill_algorithm: Default::default(),
+            uniform_fill_guard: Default::default(),
+            scheduler: Default::default(),
+            cursor: Default::default(),
         }
     }
 }
 
+/// A dirty region, ordered by the priority given to it by a [Scheduler].
 #[derive(Debug, Eq, Hash, PartialEq)]
-pub(crate) struct ByDistToFocus {
-    d: i32,
+pub(crate) struct ByPriority {
+    priority: i32,
     value: RectRegion,
 }
-impl ByDistToFocus {
-    fn of(value: RectRegion, (focus_x, focus_y): &(usize, usize)) -> Self {
-        Self {
-            d: -value.squared_distance_to((*focus_x as i32, *focus_y as i32)),
-            value,
-        }
-    }
 
-    fn pan(mut self, dx: i32, dy: i32, img: &Image) -> Self {
-        self.value.x0 -= dx;
-        self.value.y0 -= dy;
-        Self::of(self.value, &(img.width / 2, img.height / 2))
-    }
-}
-impl PartialOrd for ByDistToFocus {
-    fn partial_cmp(&self, other: &ByDistToFocus) -> Option<std::cmp::Ordering> {
+impl PartialOrd for ByPriority {
+    fn partial_cmp(&self, other: &ByPriority) -> Option<std::cmp::Ordering> {
         Some(self.cmp(other))
     }
 }
-impl Ord for ByDistToFocus {
-    fn cmp(&self, other: &ByDistToFocus) -> std::cmp::Ordering {
-        self.d.cmp(&other.d)
+impl Ord for ByPriority {
+    fn cmp(&self, other: &ByPriority) -> std::cmp::Ordering {
+        self.priority.cmp(&other.priority)
     }
 }
-impl std::ops::Deref for ByDistToFocus {
+impl std::ops::Deref for ByPriority {
     type Target = RectRegion;
     fn deref(&self) -> &<Self as std::ops::Deref>::Target {
         &self.value
     }
 }
 
+/// Computes single pixels of an [Image] for [Engine::compute].
+struct PixelComputer<'a> {
+    image: &'a mut Image,
+    top_left: Complex<f64>,
+    span: Complex<f64>,
+    /// The size of the whole view, and the position of the image in it.
+    view_size: (usize, usize),
+    offset: (usize, usize),
+    strip: Option<ExpStrip>,
+    iteration_limit: usize,
+    tracking: &'a Tracking,
+    mirror: Option<i32>,
+    uniform_fill_guard: Option<usize>,
+    iterations: u64,
+    escape_range: Option<(usize, usize)>,
+}
+
+impl PixelComputer<'_> {
+    fn point(&self, x: i32, y: i32) -> Complex<f64> {
+        if let Some(strip) = &self.strip {
+            strip.point(x, y)
+        } else {
+            let (x, y) = (x as usize + self.offset.0, y as usize + self.offset.1);
+            let c_offset_re: f64 = x as f64 * self.span.re / self.view_size.0 as f64;
+            let c_offset_im: f64 = y as f64 * self.span.im / self.view_size.1 as f64;
+            let c_offset: Complex<f64> = (c_offset_re, c_offset_im).into();
+            self.top_left + c_offset
+        }
+    }
+}
+
+impl Pixels for PixelComputer<'_> {
+    fn compute(&mut self, x: i32, y: i32) -> usize {
+        let i = x as usize + y as usize * self.image.width;
+        let c = self.point(x, y);
+
+        // A pixel whose mirror image was computed at exactly its conjugate is
+        // copied instead. It still counts as the work of computing it, so
+        // that the order in which pixels are computed, and so partial
+        // renders, don't depend on it.
+        let mirrored = self.mirror.and_then(|k| {
+            let mirrored = self.image.mirrored_index(Some(k), x, y)?;
+            let mirrored_c = self.point(x, k - y);
+            (self.image.computed[mirrored] && mirrored_c.re == c.re && mirrored_c.im == -c.im)
+                .then_some(mirrored)
+        });
+        let escape_count = if let Some(mirrored) = mirrored {
+            if self.tracking.any() {
+                self.image.orbits[i] = self.image.orbits[mirrored].conjugate(self.tracking);
+            }
+            self.image.escape_counts[mirrored]
+        } else if self.tracking.any() {
+            let (escape_count, orbit) =
+                mandelbrot::check_tracking(c, self.iteration_limit, 4.0, self.tracking);
+            self.image.orbits[i] = orbit;
+            self.iterations += escape_count as u64;
+            escape_count
+        } else {
+            let escape_count = mandelbrot::check(c, self.iteration_limit, 4.0);
+            self.iterations += escape_count as u64;
+            escape_count
+        };
+        self.image.escape_counts[i] = escape_count;
+        self.image.computed[i] = true;
+        if escape_count < self.iteration_limit {
+            let (low, high) = self
+                .escape_range
+                .get_or_insert((escape_count, escape_count));
+            *low = std::cmp::min(*low, escape_count);
+            *high = std::cmp::max(*high, escape_count);
+        }
+        escape_count
+    }
+
+    fn fillable(&self, escape_count: usize, width: usize, height: usize) -> bool {
+        // Orbit data can't be filled in, it must be computed
+        if escape_count >= self.iteration_limit {
+            !self.tracking.cycle
+        } else {
+            !self.tracking.exterior()
+                && self
+                    .uniform_fill_guard
+                    .is_some_and(|guard| std::cmp::min(width, height) >= guard)
+        }
+    }
+
+    fn fill(&mut self, x: i32, y: i32, escape_count: usize) {
+        self.image.escape_counts[x as usize + y as usize * self.image.width] = escape_count;
+    }
+}
+
 #[derive(Debug)]
 pub struct Engine {
     top_left: Complex<f64>,
     btm_right: Complex<f64>,
     image: Image,
-    dirty_regions: BinaryHeap<ByDistToFocus>,
+    /// The whole view and the tile of it that `image` is, if the engine
+    /// renders one tile. `top_left` and `btm_right` are then those of the
+    /// whole view.
+    tile: Option<(TileGrid, Tile)>,
+    dirty_regions: BinaryHeap<ByPriority>,
     zoom_focus: (usize, usize),
+    scheduler: Scheduler,
+    exploit_symmetry: bool,
+    /// Rows `y` and `mirror - y` of the image are mirror images of each other.
+    mirror: Option<i32>,
     iteration_limit: usize,
+    tracking: Tracking,
+    lighting: Option<Lighting>,
+    exterior_coloring: ExteriorColoring,
+    transfer: Transfer,
+    render_mode: RenderMode,
+    buddhabrot: Option<Buddhabrot>,
+    fill_algorithm: FillAlgorithm,
+    trace: Option<BoundaryTrace>,
+    uniform_fill_guard: Option<usize>,
+    /// The escape range of the pixels computed so far, kept up to date as
+    /// they are computed rather than measured for each render.
+    escape_range: Option<(usize, usize)>,
+    /// The escape range to stretch the gradient over, shared between the
+    /// engines of one image rather than measured by each.
+    shared_escape_range: Option<(usize, usize)>,
+    /// Pixels are laid out in a strip of the exponential map, rather than
+    /// evenly between `top_left` and `btm_right`.
+    strip: Option<ExpStrip>,
+    /// Iterations computed since the image was last reset.
+    iterations: u64,
+    /// Time spent computing since the image was last reset.
+    compute_time: Duration,
+    frame_budget: FrameBudget,
 }
 
 impl Engine {
@@ -560,6 +1556,7 @@ impl Engine {
         let mut e = Self {
             top_left: Complex::from((0, 0)),
             btm_right: Complex::from((0, 0)),
+            tile: None,
             image: Image::new(
                 *width,
                 *height,
@@ -569,12 +1566,77 @@ impl Engine {
             ),
             dirty_regions: BinaryHeap::new(),
             zoom_focus: (0, 0),
+            scheduler: *settings.scheduler.current(),
+            exploit_symmetry: mandelbrot::CONJUGATE_SYMMETRIC,
+            mirror: None,
             iteration_limit: *settings.iteration_limit.current(),
+            tracking: Self::tracking_for(
+                &settings.gradient,
+                settings.lighting.current(),
+                settings.exterior_coloring.current(),
+            ),
+            lighting: *settings.lighting.current(),
+            exterior_coloring: *settings.exterior_coloring.current(),
+            transfer: *settings.transfer.current(),
+            render_mode: *settings.render_mode.current(),
+            buddhabrot: None,
+            fill_algorithm: *settings.fill_algorithm.current(),
+            trace: None,
+            uniform_fill_guard: *settings.uniform_fill_guard.current(),
+            escape_range: None,
+            shared_escape_range: None,
+            strip: None,
+            iterations: 0,
+            compute_time: Duration::ZERO,
+            frame_budget: FrameBudget::default(),
         };
+        e.image.set_tracking(&e.tracking);
         e.update_limits(*settings.scale.current(), settings.center.current());
+        e.restart_accumulation();
         e
     }
 
+    /// An engine for one tile of the view that `settings` describe at the
+    /// size of `grid`. Its pixels are offset from the top left corner of the
+    /// whole view just as in an engine for the whole view, so that the
+    /// tiles match it exactly.
+    ///
+    /// Accumulative render modes sample the whole view at once, so they
+    /// can't be split into tiles. With [PivotScale::EscapeRange], the tiles
+    /// need a [Engine::share_escape_range] of the whole view to match.
+    pub fn new_tile(settings: &EngineSettings, grid: &TileGrid, tile: &Tile) -> Self {
+        let mut tile_settings = settings.clone().set_size(tile.width, tile.height);
+        let mut engine = Self::new(&tile_settings);
+        engine.apply_settings(&mut tile_settings);
+
+        engine.tile = Some((*grid, *tile));
+        engine.update_limits(
+            *tile_settings.scale.current(),
+            tile_settings.center.current(),
+        );
+        engine.dirtify_all();
+        engine
+    }
+
+    /// An engine for a strip of the exponential map, colored as `settings`
+    /// describe, whose center and scale are ignored. The strip is not
+    /// symmetric about the real axis even if its center is on it, so no
+    /// rows are mirrored.
+    ///
+    /// Like tiles, strips can't be rendered in accumulative render modes,
+    /// and bands of one strip need a shared escape range.
+    pub fn new_strip(settings: &EngineSettings, strip: &ExpStrip) -> Self {
+        let mut strip_settings = settings.clone().set_size(strip.width(), strip.height());
+        let mut engine = Self::new(&strip_settings);
+        engine.apply_settings(&mut strip_settings);
+
+        engine.strip = Some(*strip);
+        engine.exploit_symmetry = false;
+        engine.mirror = None;
+        engine.dirtify_all();
+        engine
+    }
+
     pub fn apply_settings(&mut self, settings: &mut EngineSettings) {
         let EngineSettings {
             size,
@@ -583,9 +1645,21 @@ impl Engine {
             iteration_limit,
             gradient,
             zoom_focus,
+            render_mode,
+            lighting,
+            exterior_coloring,
+            palette_cycle,
+            transfer,
+            fill_algorithm,
+            uniform_fill_guard,
+            scheduler,
+            cursor,
         } = settings;
 
+        let mut view_changed = false;
+
         if let Some((_, (new_width, new_height))) = size.latch() {
+            view_changed = true;
             self.set_size(
                 *new_width,
                 *new_height,
@@ -597,6 +1671,7 @@ impl Engine {
 
         match (center.latch(), zoom_focus.latch()) {
             (Some((_, new_center)), Some((_, Some(zoom_focus)))) => {
+                view_changed = true;
                 self.update_limits(*scale.current(), new_center);
                 self.zoom_focus = *zoom_focus;
             }
@@ -607,6 +1682,7 @@ impl Engine {
             }
 
             (Some((cur_center, new_center)), _) => {
+                view_changed = true;
                 let scale = *scale.current();
 
                 fn try_i32_from_f64(f: f64) -> Option<i32> {
@@ -636,19 +1712,120 @@ impl Engine {
         };
 
         if let Some((_, new_scale)) = scale.latch() {
+            view_changed = true;
             self.dirtify_all();
             self.update_limits(*new_scale, center.current());
         }
 
         if let Some((_, iteration_limit)) = iteration_limit.latch() {
+            view_changed = true;
             if *iteration_limit > self.iteration_limit {
                 self.dirtify_all();
+            } else {
+                self.image.forget_interior(*iteration_limit);
             }
             self.iteration_limit = *iteration_limit;
+            self.escape_range = self.measure_escape_range();
+            if gradient.get_pivot_scale() != PivotScale::Absolute {
+                self.image.palette = gradient.make_palette(*iteration_limit);
+            }
         }
 
         if let Some(gradient) = gradient.get_dirty() {
             self.image.palette = gradient.make_palette(*iteration_limit.current());
+            self.update_tracking(gradient);
+        };
+
+        if let Some((_, lighting)) = lighting.latch() {
+            self.lighting = *lighting;
+            self.update_tracking(gradient);
+        }
+
+        if let Some((_, exterior_coloring)) = exterior_coloring.latch() {
+            self.exterior_coloring = *exterior_coloring;
+            self.update_tracking(gradient);
+        }
+
+        if let Some((_, transfer)) = transfer.latch() {
+            self.transfer = *transfer;
+        }
+
+        if let Some((_, None)) = palette_cycle.latch() {
+            self.set_palette_phase(0);
+        }
+
+        if let Some((_, fill_algorithm)) = fill_algorithm.latch() {
+            self.abandon_trace();
+            self.fill_algorithm = *fill_algorithm;
+        }
+
+        if let Some((_, scheduler)) = scheduler.latch() {
+            self.scheduler = *scheduler;
+            self.reschedule();
+        }
+
+        if let Some((_, Some(cursor))) = cursor.latch() {
+            if self.scheduler.follows_cursor() {
+                self.zoom_focus = *cursor;
+                self.reschedule();
+            }
+        }
+
+        if let Some((_, uniform_fill_guard)) = uniform_fill_guard.latch() {
+            self.uniform_fill_guard = *uniform_fill_guard;
+            self.dirtify_all();
+        }
+
+        if let Some((_, render_mode)) = render_mode.latch() {
+            view_changed = true;
+            self.render_mode = *render_mode;
+            self.dirtify_all();
+        }
+
+        if view_changed {
+            self.restart_accumulation();
+        }
+    }
+
+    fn tracking_for(
+        gradient: &Gradient,
+        lighting: &Option<Lighting>,
+        exterior_coloring: &ExteriorColoring,
+    ) -> Tracking {
+        Tracking {
+            derivative: lighting.is_some(),
+            average: exterior_coloring.tracking().average,
+            ..gradient.tracking()
+        }
+    }
+
+    /// Start tracking the extra per-pixel values needed by the given gradient
+    /// and the current lighting, recomputing everything if that changes what
+    /// is tracked.
+    fn update_tracking(&mut self, gradient: &Gradient) {
+        let tracking = Self::tracking_for(gradient, &self.lighting, &self.exterior_coloring);
+        if tracking != self.tracking {
+            self.tracking = tracking;
+            self.image.set_tracking(&self.tracking);
+            self.dirtify_all();
+        }
+    }
+
+    /// Discard any accumulated Buddhabrot samples and start over with the
+    /// current view, if the current render mode is accumulative.
+    fn restart_accumulation(&mut self) {
+        self.reset_stats();
+        self.buddhabrot = if self.render_mode.is_accumulative() {
+            Some(Buddhabrot::new(
+                &self.render_mode,
+                self.iteration_limit,
+                self.image.width,
+                self.image.height,
+                self.top_left,
+                self.btm_right,
+            ))
+        } else {
+            None
         };
     }
 
@@ -661,29 +1838,92 @@ impl Engine {
         gradient: &Gradient,
     ) {
         self.image = Image::new(width, height, gradient.make_palette(self.iteration_limit));
+        self.image.set_tracking(&self.tracking);
         self.zoom_focus = (self.image.width / 2, self.image.height / 2);
         self.update_limits(scale, center);
         self.dirtify_all();
     }
 
+    /// The size of the whole view, of which the image may be one tile.
+    fn view_size(&self) -> (usize, usize) {
+        match &self.tile {
+            Some((grid, _)) => (grid.width, grid.height),
+            None => (self.image.width, self.image.height),
+        }
+    }
+
+    /// The position of the image in the whole view.
+    fn view_offset(&self) -> (usize, usize) {
+        self.tile.map_or((0, 0), |(_, tile)| (tile.x0, tile.y0))
+    }
+
     fn update_limits(&mut self, scale: f64, center: &Complex<f64>) {
-        let view_center: Complex<f64> = (
-            self.image.width as f64 / 2.0 * scale,
-            -(self.image.height as f64) / 2.0 * scale,
-        )
-            .into();
+        let (width, height) = self.view_size();
+        let view_center: Complex<f64> =
+            (width as f64 / 2.0 * scale, -(height as f64) / 2.0 * scale).into();
         self.top_left = *center - view_center;
         self.btm_right = *center + view_center;
+        self.mirror = self.find_mirror();
+    }
+
+    /// If the real axis is in view, and lies on or halfway between rows of
+    /// pixels, find `k` such that rows `y` and `k - y` are mirror images.
+    fn find_mirror(&self) -> Option<i32> {
+        let im_span = self.btm_right.im - self.top_left.im;
+        if !self.exploit_symmetry || im_span == 0.0 {
+            return None;
+        }
+
+        let k = -2.0 * self.top_left.im * self.view_size().1 as f64 / im_span;
+        let rounded = k.round();
+        if (k - rounded).abs() < Self::MIRROR_TOLERANCE {
+            // Some row other than the axis must have its mirror image in view
+            let height = self.image.height as i32;
+            let k = rounded as i32 - 2 * self.view_offset().1 as i32;
+            Some(k).filter(|k| (1..2 * height - 2).contains(k))
+        } else {
+            None
+        }
     }
 
     fn dirtify_all(&mut self) {
+        self.reset_stats();
+        self.trace = None;
+        self.image.computed.fill(false);
+        self.escape_range = None;
         self.dirty_regions.clear();
-        self.dirty_regions.push(ByDistToFocus::of(
-            RectRegion::new(0, 0, self.image.width as i32, self.image.height as i32),
-            &self.zoom_focus,
+        self.push_dirty(RectRegion::new(
+            0,
+            0,
+            self.image.width as i32,
+            self.image.height as i32,
         ));
     }
 
+    fn schedule(&self, region: RectRegion) -> ByPriority {
+        ByPriority {
+            priority: self.scheduler.priority(
+                &region,
+                self.zoom_focus,
+                (self.image.width, self.image.height),
+            ),
+            value: region,
+        }
+    }
+
+    fn push_dirty(&mut self, region: RectRegion) {
+        self.dirty_regions.push(self.schedule(region));
+    }
+
+    /// Re-key all dirty regions, after the scheduler or its focus changed.
+    fn reschedule(&mut self) {
+        let heap_elems: Vec<ByPriority> = self.dirty_regions.drain().collect();
+        self.dirty_regions = heap_elems
+            .into_iter()
+            .map(|elem| self.schedule(elem.value))
+            .collect();
+    }
+
     fn pan(&mut self, dx: i32, dy: i32, scale: f64, new_center: &Complex<f64>) {
         self.zoom_focus = (self.image.width / 2, self.image.height / 2);
         self.update_limits(scale, new_center);
@@ -701,84 +1941,176 @@ impl Engine {
             (self.image.height as i32 - dy, self.image.height as i32)
         };
 
-        let heap_elems: Vec<ByDistToFocus> = self.dirty_regions.drain().collect();
+        self.abandon_trace();
+
+        let heap_elems: Vec<ByPriority> = self.dirty_regions.drain().collect();
         let reheap = heap_elems
             .into_iter()
-            .map(|elem| elem.pan(dx, dy, &self.image))
+            .map(|mut elem| {
+                elem.value.x0 -= dx;
+                elem.value.y0 -= dy;
+                self.schedule(elem.value)
+            })
             .collect();
         self.dirty_regions = reheap;
 
-        self.dirty_regions.push(ByDistToFocus::of(
-            RectRegion::new(dirty_x_min, 0, dirty_x_max, self.image.height as i32),
-            &self.zoom_focus,
+        self.push_dirty(RectRegion::new(
+            dirty_x_min,
+            0,
+            dirty_x_max,
+            self.image.height as i32,
         ));
-        self.dirty_regions.push({
-            let (x0, w) = if dx < 0 {
-                (dirty_x_max, self.image.width as i32)
-            } else {
-                (0, dirty_x_min)
-            };
-            ByDistToFocus::of(
-                RectRegion::new(x0, dirty_y_min, w, dirty_y_max),
-                &self.zoom_focus,
-            )
-        });
+        let (x0, w) = if dx < 0 {
+            (dirty_x_max, self.image.width as i32)
+        } else {
+            (0, dirty_x_min)
+        };
+        self.push_dirty(RectRegion::new(x0, dirty_y_min, w, dirty_y_max));
+        self.escape_range = self.measure_escape_range();
+    }
+
+    /// Put the region of the boundary trace in progress, if any, back among
+    /// the dirty regions.
+    fn abandon_trace(&mut self) {
+        if let Some(trace) = self.trace.take() {
+            self.push_dirty(trace.into_region());
+        }
     }
 
-    pub fn image_data(&self) -> Clamped<&[u8]> {
+    /// The rendered image as RGBA bytes, row by row.
+    pub fn image_data(&self) -> &[u8] {
         self.image.image_data()
     }
 
+    pub fn get_width(&self) -> usize {
+        self.image.width
+    }
+
+    pub fn get_height(&self) -> usize {
+        self.image.height
+    }
+
+    /// The number of phases in one full cycle of the colors.
+    pub fn palette_cycle_length(&self) -> usize {
+        self.image
+            .palette
+            .spread
+            .cycle_length()
+            .unwrap_or(self.iteration_limit.max(1))
+    }
+
+    /// Shift the colors `phase` steps along the gradient, as of the next
+    /// [Self::render]. Escape counts are kept, so this is cheap.
+    pub fn set_palette_phase(&mut self, phase: usize) {
+        self.image.palette.phase = phase % self.palette_cycle_length();
+    }
+
     pub fn compute(&mut self, work_limit: usize) -> usize {
+        if let Some(buddhabrot) = &mut self.buddhabrot {
+            let work = buddhabrot.compute(work_limit);
+            self.iterations += work as u64;
+            return work;
+        }
+
         let mut total_work = 0;
-        let corner_diff = self.btm_right - self.top_left;
-        let re_span = corner_diff.re;
-        let im_span = corner_diff.im;
+        let width = self.image.width as i32;
+        let height = self.image.height as i32;
+        let (view_size, offset) = (self.view_size(), self.view_offset());
+
+        loop {
+            if let Some(trace) = &mut self.trace {
+                let mut pixels = PixelComputer {
+                    image: &mut self.image,
+                    top_left: self.top_left,
+                    span: self.btm_right - self.top_left,
+                    view_size,
+                    offset,
+                    strip: self.strip,
+                    iteration_limit: self.iteration_limit,
+                    tracking: &self.tracking,
+                    mirror: self.mirror,
+                    uniform_fill_guard: self.uniform_fill_guard,
+                    iterations: 0,
+                    escape_range: self.escape_range,
+                };
+                let (work, finished) =
+                    trace.step(&mut pixels, work_limit.saturating_sub(total_work));
+                self.iterations += pixels.iterations;
+                self.escape_range = pixels.escape_range;
+                total_work += work;
+                if !finished {
+                    return total_work;
+                }
+                self.trace = None;
+                if total_work > work_limit {
+                    return total_work;
+                }
+                continue;
+            }
+
+            let Some(dirty_region) = self.dirty_regions.pop() else {
+                break;
+            };
+
+            if self.fill_algorithm == FillAlgorithm::BoundaryTrace {
+                self.trace = Some(BoundaryTrace::new(dirty_region.clipped(width, height)));
+                continue;
+            }
+
+            let mut pixels = PixelComputer {
+                image: &mut self.image,
+                top_left: self.top_left,
+                span: self.btm_right - self.top_left,
+                view_size,
+                offset,
+                strip: self.strip,
+                iteration_limit: self.iteration_limit,
+                tracking: &self.tracking,
+                mirror: self.mirror,
+                uniform_fill_guard: self.uniform_fill_guard,
+                iterations: 0,
+                escape_range: self.escape_range,
+            };
 
-        while let Some(dirty_region) = self.dirty_regions.pop() {
             let mut none_escaped = true;
+            let mut border_escape_count = None;
+            let mut uniform_border = true;
 
             for (x, y) in dirty_region.border() {
-                if x >= 0
-                    && x < (self.image.width as i32)
-                    && y >= 0
-                    && y < (self.image.height as i32)
-                {
-                    let i = x as usize + y as usize * self.image.width;
-
-                    let c_offset_re: f64 = x as f64 * re_span / self.image.width as f64;
-                    let c_offset_im: f64 = y as f64 * im_span / self.image.height as f64;
-                    let c_offset: Complex<f64> = (c_offset_re, c_offset_im).into();
-
-                    let c = self.top_left + c_offset;
-                    let escape_count = mandelbrot::check(c, self.iteration_limit, 4.0);
-                    self.image.escape_counts[i] = escape_count;
+                if x >= 0 && x < width && y >= 0 && y < height {
+                    let escape_count = pixels.compute(x, y);
                     if escape_count < self.iteration_limit {
                         none_escaped = false;
                     }
+                    if *border_escape_count.get_or_insert(escape_count) != escape_count {
+                        uniform_border = false;
+                    }
                     total_work += escape_count;
                 }
             }
-
-            if none_escaped {
-                for (x, y) in dirty_region.interior() {
-                    if x >= 0
-                        && x < (self.image.width as i32)
-                        && y >= 0
-                        && y < (self.image.height as i32)
-                    {
-                        let i = x as usize + y as usize * self.image.width;
-                        self.image.escape_counts[i] = self.iteration_limit;
-                    }
-                }
+            self.iterations += pixels.iterations;
+            self.escape_range = pixels.escape_range;
+
+            let band_escape_count = border_escape_count.filter(|escape_count| {
+                uniform_border
+                    && *escape_count < self.iteration_limit
+                    && !self.tracking.exterior()
+                    && self.uniform_fill_guard.is_some_and(|guard| {
+                        std::cmp::min(dirty_region.w, dirty_region.h) as usize >= guard
+                    })
+            });
+
+            // Filling skips computing the cycle of each interior point
+            if none_escaped && !self.tracking.cycle {
+                self.fill_interior(&dirty_region, self.iteration_limit);
+                total_work += dirty_region.interior_len();
+            } else if let Some(escape_count) = band_escape_count {
+                self.fill_interior(&dirty_region, escape_count);
                 total_work += dirty_region.interior_len();
             } else if let Some((r1, r2, r3)) = dirty_region.trisect() {
-                self.dirty_regions
-                    .push(ByDistToFocus::of(r1, &self.zoom_focus));
-                self.dirty_regions
-                    .push(ByDistToFocus::of(r2, &self.zoom_focus));
-                self.dirty_regions
-                    .push(ByDistToFocus::of(r3, &self.zoom_focus));
+                self.push_dirty(r1);
+                self.push_dirty(r2);
+                self.push_dirty(r3);
             }
 
             if total_work > work_limit {
@@ -789,28 +2121,184 @@ impl Engine {
         total_work
     }
 
+    /// How close the real axis must be to a row, or halfway between rows, in
+    /// pixels, to copy pixels across it.
+    const MIRROR_TOLERANCE: f64 = 1e-6;
+
+    fn fill_interior(&mut self, region: &RectRegion, escape_count: usize) {
+        for (x, y) in region.interior() {
+            if x >= 0 && x < (self.image.width as i32) && y >= 0 && y < (self.image.height as i32) {
+                let i = x as usize + y as usize * self.image.width;
+                self.image.escape_counts[i] = escape_count;
+            }
+        }
+    }
+
     pub fn reset(&mut self) {
         self.dirtify_all();
     }
 
+    fn reset_stats(&mut self) {
+        self.iterations = 0;
+        self.compute_time = Duration::ZERO;
+    }
+
+    /// Compute for roughly `budget` time as measured by `clock`, and return
+    /// the work done. Work is done in chunks sized to fit the budget, and
+    /// another chunk is started only if one as long as the last still fits.
+    pub fn compute_for(&mut self, budget: Duration, clock: &impl Clock) -> usize {
+        let start = clock.now();
+        let mut total_work = 0;
+
+        loop {
+            let t0 = clock.now();
+            let work_limit = self.frame_budget.work_limit();
+            let work = self.compute(work_limit);
+            let t1 = clock.now();
+            let elapsed = t1.saturating_sub(t0);
+
+            total_work += work;
+            if work > 0 {
+                self.compute_time += elapsed;
+            }
+            self.frame_budget.update(budget, elapsed, work);
+
+            let finished = work <= work_limit;
+            if finished || t1.saturating_sub(start) + elapsed > budget {
+                return total_work;
+            }
+        }
+    }
+
+    pub fn stats(&self) -> RenderStats {
+        let total_pixels = self.image.width * self.image.height;
+        let dirty_area = if let Some(buddhabrot) = &self.buddhabrot {
+            ((1.0 - buddhabrot.progress()) * total_pixels as f64).round() as usize
+        } else {
+            let (width, height) = (self.image.width as i32, self.image.height as i32);
+            let dirty: usize = self
+                .dirty_regions
+                .iter()
+                .map(|region| {
+                    let RectRegion { w, h, .. } = region.clipped(width, height);
+                    w.max(0) as usize * h.max(0) as usize
+                })
+                .sum();
+            let tracing = self.trace.as_ref().map_or(0, BoundaryTrace::remaining);
+            std::cmp::min(dirty + tracing, total_pixels)
+        };
+        let interior_pixels = self
+            .image
+            .escape_counts
+            .iter()
+            .filter(|escape_count| **escape_count >= self.iteration_limit)
+            .count();
+
+        RenderStats {
+            total_pixels,
+            dirty_area,
+            interior_pixels,
+            iterations: self.iterations,
+            compute_time: self.compute_time,
+        }
+    }
+
+    /// The lowest and highest escape counts outside the set among the
+    /// pixels computed so far, if any.
+    pub fn escape_range(&self) -> Option<(usize, usize)> {
+        self.escape_range
+    }
+
+    /// Measure [Engine::escape_range] over all pixels that aren't waiting to
+    /// be computed, for when pixels leave the view or the set.
+    fn measure_escape_range(&self) -> Option<(usize, usize)> {
+        let (width, height) = (self.image.width as i32, self.image.height as i32);
+        let mut computed = vec![true; self.image.escape_counts.len()];
+        let pending = self.dirty_regions.iter().map(|elem| &elem.value);
+        for region in pending.chain(self.trace.as_ref().map(BoundaryTrace::region)) {
+            let RectRegion { x0, y0, w, h } = region.clipped(width, height);
+            for y in y0..y0 + h {
+                for x in x0..x0 + w {
+                    computed[x as usize + y as usize * self.image.width] = false;
+                }
+            }
+        }
+
+        let escaped = || {
+            self.image
+                .escape_counts
+                .iter()
+                .zip(&computed)
+                .filter(|(escape_count, computed)| {
+                    **computed && **escape_count < self.iteration_limit
+                })
+                .map(|(escape_count, _)| *escape_count)
+        };
+        escaped().min().zip(escaped().max())
+    }
+
+    /// Stretch the gradient over `escape_range` rather than over the escape
+    /// range of this engine's own pixels, so that the tiles or bands of one
+    /// image are colored alike. `None` goes back to measuring it.
+    pub fn share_escape_range(&mut self, escape_range: Option<(usize, usize)>) {
+        self.shared_escape_range = escape_range;
+    }
+
     pub fn render(&mut self) {
-        self.image.render_pixels(self.iteration_limit);
+        if self.image.palette.pivot_scale == PivotScale::EscapeRange {
+            if let Some(escape_range) = self.shared_escape_range.or_else(|| self.escape_range()) {
+                self.image.palette.escape_range = escape_range;
+            }
+        }
+
+        if let Some(buddhabrot) = &self.buddhabrot {
+            buddhabrot.render_pixels(
+                &mut self.image.pixels,
+                &self.image.palette,
+                self.iteration_limit,
+            );
+        } else {
+            self.image.render_pixels(
+                self.iteration_limit,
+                &self.exterior_coloring,
+                &self.transfer,
+                self.lighting.as_ref(),
+            );
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
 
+    use std::cell::Cell;
     use std::collections::hash_map::DefaultHasher;
+    use std::collections::HashSet;
 
     use std::hash::Hash;
     use std::hash::Hasher;
-
+    use std::time::Duration;
+
+    use crate::buddhabrot::RenderMode;
+    use crate::budget::Clock;
+    use crate::budget::FrameBudget;
+    use crate::color_space::InterpolationSpace;
+    use crate::easing::Easing;
+    use crate::easing::Spline;
     use crate::presets::PRESETS;
-    use crate::ByDistToFocus;
+    use crate::tiles::TileGrid;
+    use crate::ByPriority;
 
+    use super::Color;
+    use super::Decomposition;
     use super::Engine;
     use super::EngineSettings;
+    use super::ExteriorColoring;
+    use super::FillAlgorithm;
+    use super::OrbitData;
+    use super::PivotScale;
+    use super::Spread;
+    use super::Transfer;
 
     fn compute_and_render(mut settings: EngineSettings, work_limit: usize) -> u64 {
         let mut engine = Engine::new(&settings);
@@ -820,7 +2308,7 @@ mod tests {
 
         let mut hasher = DefaultHasher::new();
         engine.image_data().hash(&mut hasher);
-        let dirty_regions: Vec<ByDistToFocus> = engine.dirty_regions.into_iter().collect();
+        let dirty_regions: Vec<ByPriority> = engine.dirty_regions.into_iter().collect();
         dirty_regions.len().hash(&mut hasher);
         dirty_regions.hash(&mut hasher);
         hasher.finish()
@@ -835,6 +2323,602 @@ mod tests {
         assert_eq!(hash, EXPECTED_HASH, "Incorrect hash for default settings",);
     }
 
+    #[test]
+    fn restore_render_mode() {
+        let mut settings = EngineSettings::default()
+            .set_size(64, 48)
+            .set_render_mode(RenderMode::nebulabrot_default());
+        let mut engine = Engine::new(&settings);
+        engine.apply_settings(&mut settings);
+        assert!(engine.buddhabrot.is_some());
+
+        let restored = EngineSettings::restore(&settings.serialize().unwrap()).unwrap();
+        assert_eq!(
+            restored.get_render_mode(),
+            &RenderMode::nebulabrot_default()
+        );
+    }
+
+    #[test]
+    fn restore_exterior_coloring() {
+        let coloring = ExteriorColoring::StripeAverage { density: 3.0 };
+        let mut settings = EngineSettings::default()
+            .set_size(64, 48)
+            .set_exterior_coloring(coloring);
+        let mut engine = Engine::new(&settings);
+        engine.apply_settings(&mut settings);
+        assert_eq!(
+            engine.tracking.average,
+            Some(crate::mandelbrot::Average::Stripe { density: 3.0 })
+        );
+
+        let restored = EngineSettings::restore(&settings.serialize().unwrap()).unwrap();
+        assert_eq!(restored.get_exterior_coloring(), &coloring);
+    }
+
+    #[test]
+    fn restore_interpolation() {
+        let settings = EngineSettings::default();
+        assert!(settings.serialize().unwrap().starts_with("0:"));
+
+        let settings = settings.gradient_set_interpolation(InterpolationSpace::Oklch);
+        let restored = EngineSettings::restore(&settings.serialize().unwrap()).unwrap();
+        assert_eq!(
+            restored.get_gradient().get_interpolation(),
+            InterpolationSpace::Oklch
+        );
+    }
+
+    #[test]
+    fn restore_exact_pivots() {
+        let settings = EngineSettings::default()
+            .gradient_set_pivot_scale(PivotScale::IterationLimit)
+            .gradient_set_pivot_easing(1, Easing::EaseIn);
+        let serialized = settings.serialize().unwrap();
+        assert!(serialized.starts_with("1:"));
+
+        let restored = EngineSettings::restore(&serialized).unwrap();
+        assert_eq!(restored.get_gradient(), settings.get_gradient());
+        assert_eq!(restored.serialize().unwrap(), serialized);
+    }
+
+    #[test]
+    fn relative_pivots_follow_iteration_limit() {
+        let absolute = EngineSettings::default()
+            .set_iteration_limit(40)
+            .gradient_insert_pivot(0);
+        let relative = absolute
+            .clone()
+            .gradient_set_pivot_scale(PivotScale::IterationLimit);
+        let values = |s: &EngineSettings| -> Vec<f64> {
+            s.get_gradient()
+                .get_pivots()
+                .iter()
+                .map(|p| p.value)
+                .collect()
+        };
+        let palette =
+            |s: &EngineSettings, limit| s.get_gradient().make_palette(limit).escape_values;
+        assert_eq!(values(&relative), vec![0.0, 0.5, 1.0]);
+        assert_eq!(palette(&relative, 40), palette(&absolute, 40));
+        assert_eq!(
+            values(
+                &relative
+                    .clone()
+                    .gradient_set_pivot_scale(PivotScale::Absolute)
+            ),
+            values(&absolute)
+        );
+
+        let relative = relative.set_iteration_limit(80);
+        assert_eq!(values(&relative), vec![0.0, 0.5, 1.0]);
+        let stretched = absolute
+            .set_iteration_limit(80)
+            .gradient_set_pivot_value(1, 40.0);
+        assert_eq!(values(&stretched), vec![0.0, 40.0, 80.0]);
+        assert_eq!(palette(&relative, 80), palette(&stretched, 80));
+    }
+
+    #[test]
+    fn escape_range_stretches_gradient() {
+        let mut settings = EngineSettings::new()
+            .set_view((-0.75, 0.1), 0.2)
+            .set_size(16, 12)
+            .set_iteration_limit(40)
+            .gradient_set_pivot_scale(PivotScale::EscapeRange);
+        let mut engine = Engine::new(&settings);
+        engine.apply_settings(&mut settings);
+        engine.compute(usize::MAX);
+        engine.render();
+
+        let (low, high) = engine.image.palette.escape_range;
+        assert!(low < high && high < 40, "{low} {high}");
+        let palette = &engine.image.palette;
+        assert_eq!(palette.escape_index(low, 40), 0);
+        assert_eq!(palette.escape_index(high, 40), 39);
+        assert_eq!(palette.escape_index(40, 40), 40);
+    }
+
+    #[test]
+    fn escape_range_skips_uncomputed_pixels() {
+        let mut settings = EngineSettings::new()
+            .set_view((-0.75, 0.1), 0.02)
+            .set_size(64, 48)
+            .set_iteration_limit(200)
+            .gradient_set_pivot_scale(PivotScale::EscapeRange);
+        let mut engine = Engine::new(&settings);
+        engine.apply_settings(&mut settings);
+        engine.compute(1000);
+        assert!(!engine.stats().is_done());
+        let (partial_low, partial_high) = engine.escape_range().unwrap();
+        assert_eq!(engine.escape_range(), engine.measure_escape_range());
+
+        engine.compute(usize::MAX);
+        let (low, high) = engine.escape_range().unwrap();
+        assert!(low > 0);
+        assert!(low <= partial_low && partial_high <= high);
+        assert_eq!(engine.escape_range(), engine.measure_escape_range());
+
+        // Pixels that leave the view no longer count
+        settings = settings.pan(0, 30);
+        engine.apply_settings(&mut settings);
+        assert_eq!(engine.escape_range(), engine.measure_escape_range());
+        engine.compute(usize::MAX);
+        assert_eq!(engine.escape_range(), engine.measure_escape_range());
+    }
+
+    #[test]
+    fn spread_repeats_gradient() {
+        let settings = EngineSettings::default().set_iteration_limit(200);
+        let palette = |spread| {
+            settings
+                .clone()
+                .gradient_set_pivot_value(1, 50.0)
+                .gradient_set_spread(spread)
+                .get_gradient()
+                .make_palette(200)
+        };
+        let repeat = palette(Spread::Repeat {
+            period: 50,
+            offset: 0,
+        });
+        let shifted = palette(Spread::Repeat {
+            period: 50,
+            offset: 10,
+        });
+        let mirror = palette(Spread::Mirror {
+            period: 50,
+            offset: 0,
+        });
+        for e in 0..150 {
+            assert_eq!(repeat.get_color(e, 200), repeat.get_color(e + 50, 200));
+            assert_eq!(shifted.get_color(e, 200), repeat.get_color(e + 10, 200));
+        }
+        for e in 0..50 {
+            assert_eq!(mirror.get_color(50 - e, 200), mirror.get_color(50 + e, 200));
+            assert_eq!(mirror.get_color(e, 200), repeat.get_color(e, 200));
+        }
+        assert_ne!(repeat.get_color(40, 200), repeat.get_color(60, 200));
+
+        let settings = settings.gradient_set_spread(Spread::Mirror {
+            period: 30,
+            offset: 5,
+        });
+        let restored = EngineSettings::restore(&settings.serialize().unwrap()).unwrap();
+        assert_eq!(restored.get_gradient(), settings.get_gradient());
+    }
+
+    #[test]
+    fn easing_shapes_segments() {
+        let settings = EngineSettings::default();
+        let palette = |easing| {
+            settings
+                .clone()
+                .gradient_set_pivot_easing(1, easing)
+                .get_gradient()
+                .make_palette(50)
+        };
+        let red = |easing, e| palette(easing).get_color(e, 50).r;
+
+        assert_eq!(red(Easing::Linear, 26), 127);
+        assert_eq!(red(Easing::Smoothstep, 26), 128);
+        assert!(red(Easing::Smoothstep, 6) < red(Easing::Linear, 6));
+        assert!(red(Easing::EaseIn, 26) < red(Easing::Linear, 26));
+        assert!(red(Easing::EaseOut, 26) > red(Easing::Linear, 26));
+        assert_eq!(red(Easing::Step, 49), 0);
+    }
+
+    #[test]
+    fn splines_pass_through_pivots() {
+        let settings = EngineSettings::default()
+            .gradient_insert_pivot(0)
+            .gradient_set_pivot_color(1, "#20e040");
+        let linear = settings.get_gradient().make_palette(60);
+        for spline in Spline::ALL {
+            let settings = settings.clone().gradient_set_spline(Some(spline));
+            let palette = settings.get_gradient().make_palette(60);
+            for pivot in settings.get_gradient().get_pivots() {
+                let e = pivot.value as usize;
+                assert_eq!(palette.get_color(e + 1, 60), &pivot.color, "{spline:?}");
+            }
+            assert_ne!(palette.get_color(12, 60), linear.get_color(12, 60));
+        }
+    }
+
+    #[test]
+    fn transfer_reshapes_escape_counts() {
+        let transfers = [
+            Transfer::Identity,
+            Transfer::Log,
+            Transfer::Sqrt,
+            Transfer::Power { exponent: 2.0 },
+        ];
+        for transfer in transfers {
+            let mapped: Vec<usize> = (0..100).map(|e| transfer.apply(e, 100)).collect();
+            assert_eq!(mapped[0], 0, "{transfer:?}");
+            assert!(mapped.windows(2).all(|w| w[0] <= w[1]), "{transfer:?}");
+            assert!(mapped[99] < 100, "{transfer:?}");
+            assert_eq!(transfer.apply(100, 100), 100, "{transfer:?}");
+        }
+        assert_eq!(Transfer::Identity.apply(37, 100), 37);
+        assert_eq!(Transfer::Sqrt.apply(25, 100), 50);
+        assert_eq!(Transfer::Power { exponent: 2.0 }.apply(50, 100), 25);
+        assert!(Transfer::Log.apply(10, 1000) > 300);
+
+        let mut settings = EngineSettings::default()
+            .set_size(16, 12)
+            .set_transfer(Transfer::Power { exponent: 0.7 });
+        let mut engine = Engine::new(&settings);
+        engine.apply_settings(&mut settings);
+        assert_eq!(engine.transfer, Transfer::Power { exponent: 0.7 });
+        let restored = EngineSettings::restore(&settings.serialize().unwrap()).unwrap();
+        assert_eq!(restored.get_transfer(), &Transfer::Power { exponent: 0.7 });
+    }
+
+    #[test]
+    fn translucent_colors() {
+        assert_eq!(
+            Color::parse_hex("#ff00ff"),
+            Some(Color::of(255, 0, 255, 255))
+        );
+        assert_eq!(
+            Color::parse_hex("#ff00ff80"),
+            Some(Color::of(255, 0, 255, 128))
+        );
+        assert_eq!(Color::parse_hex("#ff00f"), None);
+        assert_eq!(Color::parse_hex("#ff00ff8g"), None);
+        assert_eq!(Color::of(255, 0, 255, 128).as_hex(), "#ff00ff");
+        assert_eq!(Color::of(255, 0, 255, 128).as_hex_with_alpha(), "#ff00ff80");
+        assert_eq!(Color::of(255, 0, 255, 255).as_hex_with_alpha(), "#ff00ff");
+
+        let mut settings = EngineSettings::default()
+            .set_size(16, 12)
+            .gradient_set_inside_color("#00000000")
+            .gradient_set_pivot_color(1, "#ff00ff80");
+        let mut engine = Engine::new(&settings);
+        engine.apply_settings(&mut settings);
+        engine.compute(usize::MAX);
+        engine.render();
+
+        let alphas: Vec<u8> = engine
+            .image_data()
+            .iter()
+            .skip(3)
+            .step_by(4)
+            .copied()
+            .collect();
+        let inside = engine
+            .image
+            .escape_counts
+            .iter()
+            .map(|count| *count == settings.get_iteration_limit());
+        assert!(alphas.contains(&0));
+        for (alpha, inside) in alphas.iter().zip(inside) {
+            assert_eq!(*alpha == 0, inside);
+            assert!(inside || *alpha >= 128);
+        }
+
+        let restored = EngineSettings::restore(&settings.serialize().unwrap()).unwrap();
+        assert_eq!(restored.get_gradient().get_inside_color().get_alpha(), 0);
+        assert_eq!(
+            restored.get_gradient().get_pivots()[1].color.get_alpha(),
+            128
+        );
+    }
+
+    #[test]
+    fn decomposition_modulates_by_final_z() {
+        let color = Color::of(200, 100, 50, 255);
+        let orbit = |final_z| OrbitData {
+            final_z,
+            ..Default::default()
+        };
+        assert_eq!(
+            Decomposition::Off.modulate(&color, &orbit((1.0, -1.0))),
+            color
+        );
+        assert_eq!(
+            Decomposition::Binary.modulate(&color, &orbit((1.0, 1.0))),
+            color
+        );
+        assert_eq!(
+            Decomposition::Binary.modulate(&color, &orbit((1.0, -1.0))),
+            Color::of(0, 0, 0, 255)
+        );
+
+        let nary = Decomposition::Nary { sectors: 4 };
+        assert_eq!(nary.modulate(&color, &orbit((1.0, 0.5))), color);
+        let third = nary.modulate(&color, &orbit((-1.0, -0.5)));
+        assert!(third.r < color.r && third.a == color.a);
+    }
+
+    #[test]
+    fn boundary_trace_matches_trisect() {
+        let presets = std::iter::once(EngineSettings::default()).chain(
+            PRESETS
+                .iter()
+                .map(|preset| EngineSettings::restore(preset.state).unwrap()),
+        );
+        // Zoomed out, the set and the bands nearest to it are an island in
+        // the outermost band, out of reach of its contours
+        let islands = [
+            ((-0.7436, 0.1318), 0.04, 100),
+            ((0.2925, 0.0148), 0.04, 1000),
+            ((-0.5, 0.3), 0.05, 100),
+            ((-0.75, 0.0), 0.025, 200),
+        ]
+        .into_iter()
+        .map(|(center, scale, iteration_limit)| {
+            EngineSettings::default()
+                .set_view(center, scale)
+                .set_iteration_limit(iteration_limit)
+        });
+        for settings in presets.chain(islands) {
+            let settings = settings.set_size(160, 120);
+            let compute = |fill_algorithm| {
+                let mut settings = settings.clone().set_fill_algorithm(fill_algorithm);
+                let mut engine = Engine::new(&settings);
+                engine.apply_settings(&mut settings);
+                let work = engine.compute(usize::MAX);
+                (engine.image.escape_counts, work)
+            };
+            let (trisect, trisect_work) = compute(FillAlgorithm::Trisect);
+            let (traced, traced_work) = compute(FillAlgorithm::BoundaryTrace);
+            let differing = trisect.iter().zip(&traced).filter(|(a, b)| a != b).count();
+            assert_eq!(differing, 0, "{:?}", settings.get_center());
+            assert!(traced_work > 0 && trisect_work > 0);
+        }
+    }
+
+    #[test]
+    fn uniform_fill() {
+        for fill_algorithm in [FillAlgorithm::Trisect, FillAlgorithm::BoundaryTrace] {
+            let compute = |guard| {
+                let mut settings = EngineSettings::default()
+                    .set_size(160, 120)
+                    .set_fill_algorithm(fill_algorithm)
+                    .set_uniform_fill_guard(guard);
+                let mut engine = Engine::new(&settings);
+                engine.apply_settings(&mut settings);
+                let work = engine.compute(usize::MAX);
+                (engine.image.escape_counts, work)
+            };
+            let (exact, exact_work) = compute(None);
+            let (unguarded, unguarded_work) = compute(Some(0));
+            let (guarded, guarded_work) = compute(Some(1000));
+
+            assert!(unguarded_work < exact_work, "{fill_algorithm:?}");
+            assert!(guarded == exact, "{fill_algorithm:?}");
+            assert_eq!(guarded_work, exact_work, "{fill_algorithm:?}");
+
+            let differing = exact.iter().zip(&unguarded).filter(|(a, b)| a != b).count();
+            assert!(differing * 100 < exact.len(), "{differing} pixels differ");
+        }
+    }
+
+    /// A clock that advances by a millisecond every time it is read.
+    #[derive(Default)]
+    struct StepClock(Cell<Duration>);
+
+    impl Clock for StepClock {
+        fn now(&self) -> Duration {
+            let now = self.0.get();
+            self.0.set(now + Duration::from_millis(1));
+            now
+        }
+    }
+
+    #[test]
+    fn compute_for_respects_budget() {
+        let mut settings = EngineSettings::default().set_size(640, 480);
+        let mut engine = Engine::new(&settings);
+        engine.apply_settings(&mut settings);
+
+        // Each chunk takes one tick of the clock, and so does starting it, so
+        // a budget of three ticks fits two chunks
+        let clock = StepClock::default();
+        engine.frame_budget = FrameBudget::default();
+        let first_limit = engine.frame_budget.work_limit();
+        let work = engine.compute_for(Duration::from_millis(3), &clock);
+        assert!(work > first_limit);
+        assert!(!engine.stats().is_done());
+        assert_eq!(engine.stats().compute_time, Duration::from_millis(2));
+
+        while !engine.stats().is_done() {
+            engine.compute_for(Duration::from_millis(3), &clock);
+        }
+        assert_eq!(engine.compute_for(Duration::from_millis(3), &clock), 0);
+    }
+
+    #[test]
+    fn tiles_match_full_render() {
+        let grid = TileGrid::new(150, 110, 64);
+        for state in [PRESETS[0].state, PRESETS[1].state] {
+            let mut settings = EngineSettings::restore(state)
+                .unwrap()
+                .set_size(grid.width, grid.height);
+            let mut full = Engine::new(&settings);
+            full.apply_settings(&mut settings);
+            full.compute(usize::MAX);
+
+            let mut differing = 0;
+            for tile in grid.tiles() {
+                let mut engine = Engine::new_tile(&settings, &grid, &tile);
+                engine.compute(usize::MAX);
+                for y in 0..tile.height {
+                    for x in 0..tile.width {
+                        let full_count =
+                            full.image.escape_counts[tile.x0 + x + (tile.y0 + y) * grid.width];
+                        if engine.image.escape_counts[x + y * tile.width] != full_count {
+                            differing += 1;
+                        }
+                    }
+                }
+            }
+            assert_eq!(differing, 0, "{state}");
+        }
+    }
+
+    #[test]
+    fn tiles_with_shared_escape_range_match_full_render() {
+        let grid = TileGrid::new(150, 110, 64);
+        let mut settings = EngineSettings::restore(PRESETS[0].state)
+            .unwrap()
+            .set_size(grid.width, grid.height)
+            .gradient_set_pivot_scale(PivotScale::EscapeRange);
+        let mut full = Engine::new(&settings);
+        full.apply_settings(&mut settings);
+        full.compute(usize::MAX);
+        full.render();
+        let escape_range = full.escape_range();
+
+        let (mut differing, mut own_ranges) = (0, HashSet::new());
+        for tile in grid.tiles() {
+            let mut engine = Engine::new_tile(&settings, &grid, &tile);
+            engine.compute(usize::MAX);
+            own_ranges.insert(engine.escape_range());
+            engine.share_escape_range(escape_range);
+            engine.render();
+            for y in 0..tile.height {
+                for x in 0..tile.width {
+                    let i = (tile.x0 + x + (tile.y0 + y) * grid.width) * 4;
+                    let j = (x + y * tile.width) * 4;
+                    if engine.image_data()[j..j + 4] != full.image_data()[i..i + 4] {
+                        differing += 1;
+                    }
+                }
+            }
+        }
+        // Measured on their own, the tiles would each be colored differently
+        assert!(own_ranges.len() > 1);
+        assert_eq!(differing, 0);
+    }
+
+    #[test]
+    fn stats_track_progress() {
+        let mut settings = EngineSettings::default().set_size(640, 480);
+        let mut engine = Engine::new(&settings);
+        engine.apply_settings(&mut settings);
+
+        let stats = engine.stats();
+        assert_eq!(stats.total_pixels, 640 * 480);
+        assert_eq!(stats.dirty_area, stats.total_pixels);
+        assert_eq!(stats.iterations, 0);
+
+        engine.compute_for(Duration::from_millis(2), &StepClock::default());
+        let partial = engine.stats();
+        assert!(partial.dirty_area < stats.dirty_area);
+        assert!(partial.iterations > 0);
+        assert!(partial.iterations_per_second() > 0.0);
+
+        engine.compute(usize::MAX);
+        let done = engine.stats();
+        assert!(done.is_done());
+        assert_eq!(done.progress(), 1.0);
+        assert!(done.iterations > partial.iterations);
+        assert!(done.interior_fraction() > 0.0 && done.interior_fraction() < 1.0);
+
+        engine.reset();
+        assert_eq!(engine.stats().iterations, 0);
+    }
+
+    #[test]
+    fn mirror_symmetry_matches_full_compute() {
+        let states = std::iter::once(EngineSettings::default().serialize().unwrap())
+            .chain(PRESETS.iter().map(|preset| preset.state.to_string()));
+        let mut mirrored_views = 0;
+        for state in states {
+            for (width, height) in [(160, 120), (161, 121)] {
+                let compute = |exploit_symmetry| {
+                    let mut settings = EngineSettings::restore(&state)
+                        .unwrap()
+                        .set_size(width, height);
+                    let mut engine = Engine::new(&settings);
+                    engine.exploit_symmetry = exploit_symmetry;
+                    engine.apply_settings(&mut settings);
+                    let mirrored = engine.mirror.is_some();
+                    engine.compute(usize::MAX);
+                    let iterations = engine.stats().iterations;
+                    (engine.image.escape_counts, iterations, mirrored)
+                };
+                let (full, full_iterations, _) = compute(false);
+                let (mirror, mirror_iterations, mirrored) = compute(true);
+                assert!(full == mirror, "{state} at {width}x{height}");
+                if mirrored {
+                    mirrored_views += 1;
+                    assert!(mirror_iterations < full_iterations);
+                }
+            }
+        }
+        assert!(mirrored_views > 0);
+    }
+
+    #[test]
+    fn mirror_symmetry_survives_panning() {
+        let compute = |exploit_symmetry| {
+            let mut settings = EngineSettings::default().set_size(160, 120);
+            let mut engine = Engine::new(&settings);
+            engine.exploit_symmetry = exploit_symmetry;
+            engine.apply_settings(&mut settings);
+            engine.compute(20_000);
+            for (dx, dy) in [(0, 7), (3, -20), (0, 50), (-5, 3)] {
+                settings = settings.pan(dx, dy);
+                engine.apply_settings(&mut settings);
+                engine.compute(20_000);
+            }
+            engine.compute(usize::MAX);
+            engine.image.escape_counts
+        };
+        assert!(compute(false) == compute(true));
+    }
+
+    #[test]
+    fn mirror_symmetry_survives_axis_leaving_view() {
+        for pans in [
+            [(0, -80), (0, 80)],
+            [(0, -80), (0, 60)],
+            [(0, -100), (0, 100)],
+        ] {
+            let compute = |exploit_symmetry| {
+                let mut settings = EngineSettings::default().set_size(160, 120);
+                let mut engine = Engine::new(&settings);
+                engine.exploit_symmetry = exploit_symmetry;
+                engine.apply_settings(&mut settings);
+                for (dx, dy) in pans {
+                    settings = settings.pan(dx, dy);
+                    engine.apply_settings(&mut settings);
+                    engine.compute(usize::MAX);
+                }
+                assert_eq!(engine.mirror.is_some(), exploit_symmetry, "{pans:?}");
+                engine.image.escape_counts
+            };
+            let full = compute(false);
+            let mirror = compute(true);
+            let differing = full.iter().zip(&mirror).filter(|(a, b)| a != b).count();
+            assert_eq!(differing, 0, "{pans:?}");
+        }
+    }
+
     #[test]
     fn render_presets() {
         const PRESET_HASHES: &[(&str, u64)] = &[
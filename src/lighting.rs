@@ -0,0 +1,86 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::Color;
+
+/// A directional light that makes the exterior of the set look like a
+/// three-dimensional surface.
+///
+/// The surface normal at each pixel is derived from the direction in which the
+/// escape potential increases, tilted 45 degrees out of the image plane.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Lighting {
+    /// Direction the light comes from, in degrees counterclockwise from the
+    /// positive real axis.
+    pub angle: f64,
+
+    /// Height of the light above the image plane, in degrees.
+    pub elevation: f64,
+
+    /// How much the lighting darkens the palette color, in `[0, 1]`.
+    pub strength: f64,
+}
+
+impl Default for Lighting {
+    fn default() -> Self {
+        Self {
+            angle: 45.0,
+            elevation: 45.0,
+            strength: 0.75,
+        }
+    }
+}
+
+impl Lighting {
+    /// Brightness in `[0, 1]` of a surface with the given unit normal.
+    fn brightness(&self, (nx, ny): (f32, f32)) -> f64 {
+        let (angle_sin, angle_cos) = self.angle.to_radians().sin_cos();
+        let (elevation_sin, elevation_cos) = self.elevation.to_radians().sin_cos();
+
+        let light = (
+            elevation_cos * angle_cos,
+            elevation_cos * angle_sin,
+            elevation_sin,
+        );
+        let dot = (f64::from(nx) * light.0 + f64::from(ny) * light.1 + light.2)
+            / std::f64::consts::SQRT_2;
+        dot.clamp(0.0, 1.0)
+    }
+
+    pub fn shade(&self, color: &Color, normal: (f32, f32)) -> Color {
+        let factor = 1.0 - self.strength + self.strength * self.brightness(normal);
+        let scale = |channel: u8| (f64::from(channel) * factor).round().clamp(0.0, 255.0) as u8;
+        Color::of(scale(color.r), scale(color.g), scale(color.b), color.a)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Lighting;
+
+    #[test]
+    fn facing_the_light_is_brightest() {
+        let lighting = Lighting {
+            angle: 0.0,
+            elevation: 45.0,
+            strength: 1.0,
+        };
+        let towards = lighting.brightness((1.0, 0.0));
+        let sideways = lighting.brightness((0.0, 1.0));
+        let away = lighting.brightness((-1.0, 0.0));
+        assert!((towards - 1.0).abs() < 1e-9);
+        assert!(towards > sideways);
+        assert!(sideways > away);
+        assert!(away >= 0.0);
+    }
+
+    #[test]
+    fn zero_strength_is_identity() {
+        let lighting = Lighting {
+            strength: 0.0,
+            ..Default::default()
+        };
+        let color = crate::Color::of(10, 20, 30, 40);
+        assert_eq!(lighting.shade(&color, (-1.0, 0.0)), color);
+    }
+}
@@ -0,0 +1,35 @@
+//! Logging for code that doesn't know whether it runs in a browser. Messages
+//! go to a sink chosen by the application, by default standard error.
+
+use std::sync::RwLock;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Level {
+    Info,
+    Error,
+}
+
+/// Somewhere to write log messages.
+pub type LogSink = fn(Level, &str);
+
+static SINK: RwLock<LogSink> = RwLock::new(log_to_stderr);
+
+pub fn log_to_stderr(level: Level, message: &str) {
+    match level {
+        Level::Info => eprintln!("{message}"),
+        Level::Error => eprintln!("Error: {message}"),
+    }
+}
+
+/// Send all further log messages to `sink`.
+pub fn set_sink(sink: LogSink) {
+    if let Ok(mut current) = SINK.write() {
+        *current = sink;
+    }
+}
+
+pub fn write(level: Level, message: &str) {
+    if let Ok(sink) = SINK.read() {
+        sink(level, message);
+    }
+}
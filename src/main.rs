@@ -3,5 +3,6 @@ use yew::Renderer;
 use fraktal::components::app::App;
 
 fn main() {
+    fraktal::log::set_sink(fraktal::components::log_to_console);
     Renderer::<App>::new().render();
 }
@@ -1,5 +1,24 @@
 use super::complex::Complex;
 
+/// Longest attracting cycle that [check_tracking] will look for.
+const MAX_PERIOD: usize = 1024;
+
+/// How close (squared) an orbit must return to itself to count as a cycle.
+const CYCLE_EPSILON_SQUARED: f64 = 1e-12;
+
+/// Orbit averages keep iterating past the escape radius until this radius
+/// (squared), so that the smooth interpolation between averages is smooth.
+const AVERAGE_BAILOUT_SQUARED: f64 = 1e8;
+
+/// Most extra iterations to take after escape when computing orbit averages.
+const MAX_AVERAGE_TAIL: usize = 64;
+
+/// Whether the escape count and orbit data of `conj(c)` are those of `c`,
+/// conjugated. This lets [crate::Engine] compute only one side of the real
+/// axis. A formula that is not symmetric in conjugation must opt out by
+/// setting this to `false`.
+pub const CONJUGATE_SYMMETRIC: bool = true;
+
 pub fn check(c: Complex<f64>, iteration_limit: usize, escape_abs_squared: f64) -> usize {
     let mut z = c;
 
@@ -14,3 +33,392 @@ pub fn check(c: Complex<f64>, iteration_limit: usize, escape_abs_squared: f64) -
 
     iteration_limit
 }
+
+/// Which extra per-pixel values [check_tracking] should compute.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Tracking {
+    pub cycle: bool,
+    pub derivative: bool,
+    pub average: Option<Average>,
+    pub final_z: bool,
+}
+
+impl Tracking {
+    pub fn any(&self) -> bool {
+        self.cycle || self.exterior()
+    }
+
+    /// Whether any values are tracked for orbits that escape.
+    pub fn exterior(&self) -> bool {
+        self.derivative || self.average.is_some() || self.final_z
+    }
+}
+
+/// A quantity averaged over the orbit of escaping points.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Average {
+    /// Mean of `(1 + sin(density * arg z)) / 2`.
+    Stripe { density: f64 },
+
+    /// Mean of the normalized turning angle between consecutive orbit steps.
+    Curvature,
+}
+
+/// Running sum of an [Average] over an orbit.
+struct Averager {
+    average: Average,
+    sum: f64,
+    last: f64,
+    terms: usize,
+    prev: [Complex<f64>; 2],
+}
+
+impl Averager {
+    fn new(average: Average, z0: Complex<f64>) -> Self {
+        Self {
+            average,
+            sum: 0.0,
+            last: 0.0,
+            terms: 0,
+            prev: [z0, z0],
+        }
+    }
+
+    fn arg(z: Complex<f64>) -> f64 {
+        z.im.atan2(z.re)
+    }
+
+    fn push(&mut self, z: Complex<f64>) {
+        let term = match self.average {
+            Average::Stripe { density } => Some(0.5 + 0.5 * (density * Self::arg(z)).sin()),
+            Average::Curvature => {
+                let [prev, prev2] = self.prev;
+                if prev != prev2 {
+                    let a = z - prev;
+                    let b = prev - prev2;
+                    Some(Self::arg(a * Complex::from((b.re, -b.im))).abs() / std::f64::consts::PI)
+                } else {
+                    None
+                }
+            }
+        };
+        self.prev = [z, self.prev[0]];
+
+        if let Some(term) = term {
+            self.sum += term;
+            self.last = term;
+            self.terms += 1;
+        }
+    }
+
+    /// Interpolate between the averages with and without the last term, by
+    /// how far past the bailout radius the final `z` is.
+    fn finish(&self, z_abs_squared: f64) -> f32 {
+        match self.terms {
+            0 => 0.0,
+            1 => self.sum as f32,
+            n => {
+                let avg = self.sum / n as f64;
+                let prev_avg = (self.sum - self.last) / (n - 1) as f64;
+                let bailout_log = AVERAGE_BAILOUT_SQUARED.ln() / 2.0;
+                let z_log = z_abs_squared.ln() / 2.0;
+                let frac = (1.0 + bailout_log.log2() - z_log.log2()).clamp(0.0, 1.0);
+                (frac * avg + (1.0 - frac) * prev_avg) as f32
+            }
+        }
+    }
+}
+
+/// Extra per-pixel values computed alongside the escape count.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct OrbitData {
+    /// Period of the attracting cycle the orbit converges to, or 0 if none was
+    /// found.
+    pub period: u32,
+
+    /// Magnitude of the multiplier of the attracting cycle. Ranges from 0 at
+    /// the center of a hyperbolic component to 1 at its boundary.
+    pub multiplier: f32,
+
+    /// Unit vector `z / (dz/dc)` at escape, the direction in which the escape
+    /// potential increases. Zero if the derivative was not tracked.
+    pub normal: (f32, f32),
+
+    /// Smoothly interpolated orbit average in `[0, 1]`, or zero if no average
+    /// was tracked.
+    pub average: f32,
+
+    /// The first `z` outside the escape radius, or zero if the orbit did not
+    /// escape or the final `z` was not tracked.
+    pub final_z: (f32, f32),
+}
+
+impl OrbitData {
+    /// The orbit data of `conj(c)`, given that this is the orbit data of `c`.
+    pub fn conjugate(&self, tracking: &Tracking) -> Self {
+        let (nx, ny) = self.normal;
+        let (re, im) = self.final_z;
+        Self {
+            normal: (nx, -ny),
+            average: match tracking.average {
+                // sin(density * arg z) changes sign
+                Some(Average::Stripe { .. }) => 1.0 - self.average,
+                Some(Average::Curvature) | None => self.average,
+            },
+            final_z: (re, -im),
+            ..*self
+        }
+    }
+}
+
+pub fn check_tracking(
+    c: Complex<f64>,
+    iteration_limit: usize,
+    escape_abs_squared: f64,
+    tracking: &Tracking,
+) -> (usize, OrbitData) {
+    let mut z = c;
+    let mut der: Complex<f64> = (1.0, 0.0).into();
+    let mut averager = tracking.average.map(|average| Averager::new(average, z));
+
+    for i in 0..iteration_limit {
+        let (abs, z2) = z.abs_squared_and_square();
+        if abs >= escape_abs_squared {
+            return (
+                i,
+                OrbitData {
+                    normal: if tracking.derivative {
+                        normal(z, der)
+                    } else {
+                        (0.0, 0.0)
+                    },
+                    average: averager
+                        .map(|averager| finish_average(averager, z, abs, c))
+                        .unwrap_or(0.0),
+                    final_z: if tracking.final_z {
+                        (z.re as f32, z.im as f32)
+                    } else {
+                        (0.0, 0.0)
+                    },
+                    ..Default::default()
+                },
+            );
+        }
+
+        if tracking.derivative {
+            der = der * z * 2.0 + Complex::from((1.0, 0.0));
+        }
+        z = z2 + c;
+
+        if let Some(averager) = &mut averager {
+            averager.push(z);
+        }
+    }
+
+    let data = if tracking.cycle {
+        find_cycle(z, c, std::cmp::min(iteration_limit, MAX_PERIOD))
+    } else {
+        OrbitData::default()
+    };
+
+    (iteration_limit, data)
+}
+
+/// Keep iterating an escaped orbit out to [AVERAGE_BAILOUT_SQUARED], then
+/// compute the interpolated average.
+fn finish_average(
+    mut averager: Averager,
+    mut z: Complex<f64>,
+    mut abs: f64,
+    c: Complex<f64>,
+) -> f32 {
+    for _ in 0..MAX_AVERAGE_TAIL {
+        if abs >= AVERAGE_BAILOUT_SQUARED {
+            break;
+        }
+        let (_, z2) = z.abs_squared_and_square();
+        z = z2 + c;
+        averager.push(z);
+        (abs, _) = z.abs_squared_and_square();
+    }
+    averager.finish(abs)
+}
+
+/// Compute the unit vector in the direction of `z / der`.
+fn normal(z: Complex<f64>, der: Complex<f64>) -> (f32, f32) {
+    // z / der has the same direction as z * conj(der)
+    let u = z * Complex::from((der.re, -der.im));
+    let (abs, _) = u.abs_squared_and_square();
+    if abs > 0.0 {
+        let abs = abs.sqrt();
+        ((u.re / abs) as f32, (u.im / abs) as f32)
+    } else {
+        (0.0, 0.0)
+    }
+}
+
+/// Find the period and multiplier magnitude of the cycle that `z0` has
+/// converged to, if any.
+fn find_cycle(z0: Complex<f64>, c: Complex<f64>, max_period: usize) -> OrbitData {
+    let mut z = z0;
+    let mut multiplier: Complex<f64> = (1.0, 0.0).into();
+
+    for period in 1..=max_period {
+        multiplier = multiplier * z * 2.0;
+        let (_, z2) = z.abs_squared_and_square();
+        z = z2 + c;
+
+        let (dist, _) = (z - z0).abs_squared_and_square();
+        if dist < CYCLE_EPSILON_SQUARED {
+            let (abs, _) = multiplier.abs_squared_and_square();
+            return OrbitData {
+                period: period as u32,
+                multiplier: abs.sqrt().min(1.0) as f32,
+                ..Default::default()
+            };
+        }
+    }
+
+    OrbitData::default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::check_tracking;
+    use super::Average;
+    use super::Tracking;
+    use crate::complex::Complex;
+
+    const TRACK_CYCLE: Tracking = Tracking {
+        cycle: true,
+        derivative: false,
+        average: None,
+        final_z: false,
+    };
+
+    const TRACK_DERIVATIVE: Tracking = Tracking {
+        cycle: false,
+        derivative: true,
+        average: None,
+        final_z: false,
+    };
+
+    #[test]
+    fn cycle_period() {
+        for (c, expected_period) in [
+            ((0.0, 0.0), 1),
+            ((0.1, 0.1), 1),
+            ((-1.0, 0.0), 2),
+            ((-0.1, 0.75), 3),
+            ((-1.31, 0.0), 4),
+        ] {
+            let (escape_count, data) = check_tracking(Complex::from(c), 1000, 4.0, &TRACK_CYCLE);
+            assert_eq!(escape_count, 1000, "c = {c:?}");
+            assert_eq!(data.period, expected_period, "c = {c:?}");
+            assert!(data.multiplier < 1.0, "c = {c:?}");
+        }
+    }
+
+    #[test]
+    fn superattracting_multiplier() {
+        let (_, data) = check_tracking(Complex::from((0.0, 0.0)), 100, 4.0, &TRACK_CYCLE);
+        assert_eq!(data.multiplier, 0.0);
+
+        let (_, data) = check_tracking(Complex::from((-1.0, 0.0)), 100, 4.0, &TRACK_CYCLE);
+        assert_eq!(data.multiplier, 0.0);
+    }
+
+    #[test]
+    fn normal_points_outward() {
+        for c in [(1.0, 0.0), (0.0, 1.5), (-2.5, 0.0), (0.0, -1.5)] {
+            let (escape_count, data) =
+                check_tracking(Complex::from(c), 100, 4.0, &TRACK_DERIVATIVE);
+            assert!(escape_count < 100);
+            let (nx, ny) = data.normal;
+            assert!((nx * nx + ny * ny - 1.0).abs() < 1e-5, "c = {c:?}");
+            assert!(
+                f64::from(nx) * c.0 + f64::from(ny) * c.1 > 0.0,
+                "c = {c:?}, normal = {:?}",
+                data.normal
+            );
+        }
+    }
+
+    #[test]
+    fn averages_are_normalized() {
+        for average in [Average::Stripe { density: 5.0 }, Average::Curvature] {
+            let tracking = Tracking {
+                average: Some(average),
+                ..Default::default()
+            };
+            for c in [
+                (0.5, 0.5),
+                (-0.75, 0.1),
+                (-1.8, 0.01),
+                (0.26, 0.0),
+                (2.0, 2.0),
+            ] {
+                let (escape_count, data) = check_tracking(Complex::from(c), 1000, 4.0, &tracking);
+                assert!(escape_count < 1000, "c = {c:?}");
+                assert!(
+                    (0.0..=1.0).contains(&data.average),
+                    "{average:?}, c = {c:?}: {}",
+                    data.average
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn stripe_average_is_continuous() {
+        let tracking = Tracking {
+            average: Some(Average::Stripe { density: 3.0 }),
+            ..Default::default()
+        };
+        let (_, a) = check_tracking(Complex::from((0.5, 0.5)), 1000, 4.0, &tracking);
+        let (_, b) = check_tracking(Complex::from((0.5, 0.500001)), 1000, 4.0, &tracking);
+        assert!((a.average - b.average).abs() < 1e-3);
+    }
+
+    #[test]
+    fn final_z_is_outside_escape_radius() {
+        let tracking = Tracking {
+            final_z: true,
+            ..Default::default()
+        };
+        for c in [(0.5, 0.5), (0.5, -0.5), (-2.5, 0.0), (0.0, 1.5)] {
+            let (escape_count, data) = check_tracking(Complex::from(c), 100, 4.0, &tracking);
+            assert!(escape_count < 100);
+            let (re, im) = data.final_z;
+            assert!(re * re + im * im >= 4.0, "c = {c:?}");
+        }
+
+        let (_, data) = check_tracking(Complex::from((0.0, 0.0)), 100, 4.0, &tracking);
+        assert_eq!(data.final_z, (0.0, 0.0));
+    }
+
+    #[test]
+    fn conjugate_symmetry() {
+        let tracking = Tracking {
+            cycle: true,
+            derivative: true,
+            average: Some(Average::Curvature),
+            final_z: true,
+        };
+        for (re, im) in [(0.5, 0.5), (-0.1, 0.75), (-0.75, 0.1), (0.26, 0.001)] {
+            let (count, data) = check_tracking(Complex::from((re, im)), 1000, 4.0, &tracking);
+            let (conj_count, conj_data) =
+                check_tracking(Complex::from((re, -im)), 1000, 4.0, &tracking);
+            assert_eq!(count, conj_count);
+            assert_eq!(data.conjugate(&tracking), conj_data, "c = ({re}, {im})");
+        }
+    }
+
+    #[test]
+    fn escaping_has_no_cycle() {
+        let (escape_count, data) =
+            check_tracking(Complex::from((1.0, 1.0)), 100, 4.0, &TRACK_CYCLE);
+        assert!(escape_count < 100);
+        assert_eq!(data.period, 0);
+    }
+}
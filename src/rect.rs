@@ -96,7 +96,19 @@ impl RectRegion {
         }
     }
 
-    pub fn border(&self) -> RectRegionBorder {
+    /// The part of this region that lies within a `width` by `height` image.
+    pub fn clipped(&self, width: i32, height: i32) -> RectRegion {
+        let x0 = self.x0.clamp(0, width);
+        let y0 = self.y0.clamp(0, height);
+        RectRegion::new(
+            x0,
+            y0,
+            (self.x0 + self.w).clamp(0, width) - x0,
+            (self.y0 + self.h).clamp(0, height) - y0,
+        )
+    }
+
+    pub fn border(&self) -> RectRegionBorder<'_> {
         RectRegionBorder::new(self)
     }
 
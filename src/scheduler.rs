@@ -0,0 +1,130 @@
+//! The order in which [crate::Engine::compute] works through dirty regions,
+//! which decides what the user gets to see first.
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::rect::RectRegion;
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub enum Scheduler {
+    /// Closest to the focus first. The focus is the point zoomed around, or
+    /// the center of the image.
+    #[default]
+    FocusFirst,
+
+    /// Outwards from the center of the image, sweeping around each ring.
+    Spiral,
+
+    /// Top to bottom, left to right.
+    Scanline,
+
+    /// In an arbitrary order that is the same every time.
+    Random,
+
+    /// Closest to the focus first, where the focus follows the mouse cursor.
+    CursorFollow,
+}
+
+impl Scheduler {
+    /// Width in pixels of each ring of [Self::Spiral].
+    const SPIRAL_PITCH: f64 = 32.0;
+
+    /// Number of distinct angles in each ring of [Self::Spiral].
+    const SPIRAL_STEPS: f64 = 1024.0;
+
+    /// Whether the focus should follow the mouse cursor.
+    pub fn follows_cursor(&self) -> bool {
+        *self == Self::CursorFollow
+    }
+
+    /// The priority of a region in an image of the given size. Regions with
+    /// higher priority are computed first.
+    pub fn priority(
+        &self,
+        region: &RectRegion,
+        (focus_x, focus_y): (usize, usize),
+        (width, height): (usize, usize),
+    ) -> i32 {
+        match self {
+            Self::FocusFirst | Self::CursorFollow => {
+                -region.squared_distance_to((focus_x as i32, focus_y as i32))
+            }
+
+            Self::Spiral => {
+                let dx = f64::from(region.x0) + f64::from(region.w) / 2.0 - width as f64 / 2.0;
+                let dy = f64::from(region.y0) + f64::from(region.h) / 2.0 - height as f64 / 2.0;
+                let ring = (dx.hypot(dy) / Self::SPIRAL_PITCH).floor();
+                let turns = dy.atan2(dx).rem_euclid(std::f64::consts::TAU) / std::f64::consts::TAU;
+                -((ring * Self::SPIRAL_STEPS + (turns * Self::SPIRAL_STEPS).floor()) as i32)
+            }
+
+            Self::Scanline => region
+                .y0
+                .saturating_mul(width as i32)
+                .saturating_add(region.x0)
+                .saturating_neg(),
+
+            Self::Random => {
+                let mut hash = [region.x0, region.y0, region.w, region.h]
+                    .into_iter()
+                    .fold(0x811c_9dc5_u32, |hash, v| {
+                        (hash ^ v as u32).wrapping_mul(0x0100_0193)
+                    });
+                hash ^= hash >> 15;
+                hash = hash.wrapping_mul(0x2c1b_3c6d);
+                hash ^= hash >> 12;
+                (hash >> 1) as i32
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Scheduler;
+    use crate::rect::RectRegion;
+
+    const SIZE: (usize, usize) = (100, 80);
+    const CENTER: (usize, usize) = (50, 40);
+
+    fn priority(scheduler: Scheduler, x0: i32, y0: i32) -> i32 {
+        scheduler.priority(&RectRegion::new(x0, y0, 4, 4), CENTER, SIZE)
+    }
+
+    #[test]
+    fn scanline_order() {
+        assert!(priority(Scheduler::Scanline, 0, 0) > priority(Scheduler::Scanline, 90, 0));
+        assert!(priority(Scheduler::Scanline, 90, 0) > priority(Scheduler::Scanline, 0, 10));
+    }
+
+    #[test]
+    fn spiral_starts_at_center() {
+        let center = priority(Scheduler::Spiral, 48, 38);
+        for (x0, y0) in [(0, 0), (90, 0), (0, 70), (90, 70), (90, 38)] {
+            assert!(center > priority(Scheduler::Spiral, x0, y0), "({x0}, {y0})");
+        }
+    }
+
+    #[test]
+    fn focus_first_prefers_focus() {
+        assert_eq!(priority(Scheduler::FocusFirst, 48, 38), 0);
+        assert!(priority(Scheduler::FocusFirst, 0, 0) < 0);
+        assert_eq!(
+            priority(Scheduler::FocusFirst, 0, 0),
+            priority(Scheduler::CursorFollow, 0, 0)
+        );
+    }
+
+    #[test]
+    fn random_is_deterministic() {
+        assert_eq!(
+            priority(Scheduler::Random, 10, 20),
+            priority(Scheduler::Random, 10, 20)
+        );
+        assert_ne!(
+            priority(Scheduler::Random, 10, 20),
+            priority(Scheduler::Random, 20, 10)
+        );
+    }
+}
@@ -0,0 +1,187 @@
+//! The binary layout of state strings, as in shared links and the presets.
+//!
+//! The settings that fraktal started out with come first, laid out as they
+//! always have been. Settings added since follow in an extension, which is
+//! left out when all of them are at their defaults. States that don't use
+//! the newer settings therefore serialize just as they always have, and
+//! states from before the extension restore with the defaults. States with
+//! the extension are marked as such by their version prefix, since states
+//! from even older versions may have other data after the base settings.
+
+use std::rc::Rc;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::buddhabrot::RenderMode;
+use crate::color_space::InterpolationSpace;
+use crate::complex::Complex;
+use crate::easing::Easing;
+use crate::easing::Spline;
+use crate::lighting::Lighting;
+use crate::utils;
+use crate::Color;
+use crate::Decomposition;
+use crate::EngineSettings;
+use crate::ExteriorColoring;
+use crate::Gradient;
+use crate::GradientPivot;
+use crate::InteriorColoring;
+use crate::PivotScale;
+use crate::Spread;
+use crate::Transfer;
+
+#[derive(Deserialize, Serialize)]
+struct BasePivot {
+    /// Pivots used to be whole escape counts. Other values are rounded
+    /// here, and kept exactly in the [Extension].
+    value: usize,
+    color: Color,
+}
+
+#[derive(Deserialize, Serialize)]
+struct BaseGradient {
+    inside: Color,
+    root: Color,
+    pivots: Vec<BasePivot>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct Base {
+    center: Complex<f64>,
+    scale: f64,
+    iteration_limit: usize,
+    gradient: BaseGradient,
+}
+
+/// The settings added after the [Base] layout. Settings added later go in
+/// another section after this one, under another version prefix.
+#[derive(Default, Deserialize, PartialEq, Serialize)]
+struct Extension {
+    /// The exact value and easing of each pivot, if any of them is not a
+    /// whole escape count or not linear.
+    pivots: Vec<(f64, Easing)>,
+    interior: InteriorColoring,
+    decomposition: Decomposition,
+    interpolation: InterpolationSpace,
+    pivot_scale: PivotScale,
+    spread: Spread,
+    spline: Option<Spline>,
+    render_mode: RenderMode,
+    lighting: Option<Lighting>,
+    exterior_coloring: ExteriorColoring,
+    palette_cycle: Option<f64>,
+    transfer: Transfer,
+}
+
+/// Encode `settings`, and tell whether the extension was needed.
+pub fn encode(settings: &EngineSettings) -> Result<(Vec<u8>, bool), bincode::Error> {
+    let gradient = settings.gradient.get();
+    let base = Base {
+        center: *settings.center.current(),
+        scale: *settings.scale.current(),
+        iteration_limit: *settings.iteration_limit.current(),
+        gradient: BaseGradient {
+            inside: gradient.inside,
+            root: gradient.root,
+            pivots: gradient
+                .pivots
+                .iter()
+                .map(|pivot| BasePivot {
+                    value: pivot.value.round() as usize,
+                    color: pivot.color,
+                })
+                .collect(),
+        },
+    };
+
+    let exact_pivots = gradient
+        .pivots
+        .iter()
+        .all(|pivot| pivot.value == pivot.value.round().max(0.0) && pivot.easing == Easing::Linear);
+    let extension = Extension {
+        pivots: if exact_pivots {
+            Vec::new()
+        } else {
+            gradient
+                .pivots
+                .iter()
+                .map(|pivot| (pivot.value, pivot.easing))
+                .collect()
+        },
+        interior: gradient.interior,
+        decomposition: gradient.decomposition,
+        interpolation: gradient.interpolation,
+        pivot_scale: gradient.pivot_scale,
+        spread: gradient.spread,
+        spline: gradient.spline,
+        render_mode: *settings.render_mode.current(),
+        lighting: *settings.lighting.current(),
+        exterior_coloring: *settings.exterior_coloring.current(),
+        palette_cycle: *settings.palette_cycle.current(),
+        transfer: *settings.transfer.current(),
+    };
+
+    let mut bin = bincode::serialize(&base)?;
+    let extended = !utils::is_default(&extension);
+    if extended {
+        bin.extend(bincode::serialize(&extension)?);
+    }
+    Ok((bin, extended))
+}
+
+/// Decode settings, with the extension if `extended` and otherwise ignoring
+/// anything after the base settings.
+pub fn decode(bin: &[u8], extended: bool) -> Result<EngineSettings, bincode::Error> {
+    let mut rest = bin;
+    let base: Base = bincode::deserialize_from(&mut rest)?;
+    let extension: Extension = if extended {
+        bincode::deserialize(rest)?
+    } else {
+        Extension::default()
+    };
+
+    let exact_pivots = extension.pivots.len() == base.gradient.pivots.len();
+    let pivots = base
+        .gradient
+        .pivots
+        .into_iter()
+        .enumerate()
+        .map(|(i, BasePivot { value, color })| {
+            let (value, easing) = if exact_pivots {
+                extension.pivots[i]
+            } else {
+                (value as f64, Easing::Linear)
+            };
+            GradientPivot {
+                value,
+                color,
+                easing,
+            }
+        })
+        .collect();
+
+    Ok(EngineSettings {
+        center: base.center.into(),
+        scale: base.scale.into(),
+        iteration_limit: base.iteration_limit.into(),
+        gradient: Rc::new(Gradient {
+            inside: base.gradient.inside,
+            root: base.gradient.root,
+            pivots,
+            interior: extension.interior,
+            decomposition: extension.decomposition,
+            interpolation: extension.interpolation,
+            pivot_scale: extension.pivot_scale,
+            spread: extension.spread,
+            spline: extension.spline,
+        })
+        .into(),
+        render_mode: extension.render_mode.into(),
+        lighting: extension.lighting.into(),
+        exterior_coloring: extension.exterior_coloring.into(),
+        palette_cycle: extension.palette_cycle.into(),
+        transfer: extension.transfer.into(),
+        ..Default::default()
+    })
+}
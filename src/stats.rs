@@ -0,0 +1,72 @@
+use std::time::Duration;
+
+/// How far [crate::Engine] has come in rendering the current image.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RenderStats {
+    /// Number of pixels in the image.
+    pub total_pixels: usize,
+
+    /// Number of pixels that are yet to be computed.
+    pub dirty_area: usize,
+
+    /// Number of pixels currently inside the set.
+    pub interior_pixels: usize,
+
+    /// Number of iterations computed since the image was last reset.
+    pub iterations: u64,
+
+    /// Time spent computing since the image was last reset.
+    pub compute_time: Duration,
+}
+
+impl RenderStats {
+    pub fn finalized_pixels(&self) -> usize {
+        self.total_pixels.saturating_sub(self.dirty_area)
+    }
+
+    /// Fraction of the image that is done, in `[0, 1]`.
+    pub fn progress(&self) -> f64 {
+        if self.total_pixels == 0 {
+            1.0
+        } else {
+            self.finalized_pixels() as f64 / self.total_pixels as f64
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.dirty_area == 0
+    }
+
+    /// Fraction of the image that is inside the set, in `[0, 1]`.
+    pub fn interior_fraction(&self) -> f64 {
+        if self.total_pixels == 0 {
+            0.0
+        } else {
+            self.interior_pixels as f64 / self.total_pixels as f64
+        }
+    }
+
+    pub fn iterations_per_second(&self) -> f64 {
+        let seconds = self.compute_time.as_secs_f64();
+        if seconds > 0.0 {
+            self.iterations as f64 / seconds
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Format a number of iterations per second with an SI prefix, for example
+/// `"12.3 M iterations/s"`.
+pub fn format_throughput(iterations_per_second: f64) -> String {
+    let (value, prefix) = if iterations_per_second >= 1e9 {
+        (iterations_per_second / 1e9, "G ")
+    } else if iterations_per_second >= 1e6 {
+        (iterations_per_second / 1e6, "M ")
+    } else if iterations_per_second >= 1e3 {
+        (iterations_per_second / 1e3, "k ")
+    } else {
+        return format!("{iterations_per_second:.0} iterations/s");
+    };
+    format!("{value:.1} {prefix}iterations/s")
+}
@@ -0,0 +1,246 @@
+//! Rendering images too large for one [crate::Engine], in tiles that each get
+//! an engine of their own from [crate::Engine::new_tile].
+
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Write;
+use std::path::Path;
+
+use crate::export::PngPosition;
+
+/// One tile of a [TileGrid], in pixels of the whole image.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Tile {
+    pub col: usize,
+    pub row: usize,
+    pub x0: usize,
+    pub y0: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+/// A `width` by `height` image split into square tiles. Tiles in the last
+/// column and row are smaller if the size is not a multiple of `tile_size`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TileGrid {
+    pub width: usize,
+    pub height: usize,
+    pub tile_size: usize,
+}
+
+impl TileGrid {
+    pub fn new(width: usize, height: usize, tile_size: usize) -> Self {
+        Self {
+            width,
+            height,
+            tile_size: tile_size.max(1),
+        }
+    }
+
+    pub fn columns(&self) -> usize {
+        self.width.div_ceil(self.tile_size)
+    }
+
+    pub fn rows(&self) -> usize {
+        self.height.div_ceil(self.tile_size)
+    }
+
+    pub fn len(&self) -> usize {
+        self.columns() * self.rows()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn tile(&self, col: usize, row: usize) -> Tile {
+        let x0 = col * self.tile_size;
+        let y0 = row * self.tile_size;
+        Tile {
+            col,
+            row,
+            x0,
+            y0,
+            width: std::cmp::min(self.tile_size, self.width - x0),
+            height: std::cmp::min(self.tile_size, self.height - y0),
+        }
+    }
+
+    /// The tiles in one row, left to right.
+    pub fn row(&self, row: usize) -> impl Iterator<Item = Tile> + '_ {
+        (0..self.columns()).map(move |col| self.tile(col, row))
+    }
+
+    /// All tiles, row by row.
+    pub fn tiles(&self) -> impl Iterator<Item = Tile> + '_ {
+        (0..self.rows()).flat_map(move |row| self.row(row))
+    }
+}
+
+/// A file listing the finished rows of tiles of a render, and how far the
+/// output got with each, so that an interrupted render can resume where it
+/// left off. It also keeps the escape range measured over all tiles, if the
+/// render needs one, so that it is measured only once.
+#[derive(Debug)]
+pub struct Checkpoint {
+    file: File,
+    finished: Option<(usize, PngPosition)>,
+    escape_range: Option<(usize, usize)>,
+}
+
+impl Checkpoint {
+    const VERSION: &'static str = "fraktal-tiles 2";
+
+    fn header(state: &str, grid: &TileGrid) -> [String; 3] {
+        [
+            Self::VERSION.to_string(),
+            format!("state {state}"),
+            format!("size {} {} {}", grid.width, grid.height, grid.tile_size),
+        ]
+    }
+
+    /// Open the checkpoint at `path`, or create it if it doesn't exist. Fails
+    /// if the checkpoint was made for a render of different settings.
+    pub fn open(path: &Path, state: &str, grid: &TileGrid) -> io::Result<Self> {
+        let header = Self::header(state, grid);
+        let mut finished = None;
+        let mut escape_range = None;
+
+        if path.exists() {
+            let mut lines = BufReader::new(File::open(path)?).lines();
+            for expected in &header {
+                if lines.next().transpose()?.as_ref() != Some(expected) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("{} is a checkpoint for a different render", path.display()),
+                    ));
+                }
+            }
+            for line in lines {
+                let line = line?;
+                // A line cut short by an interruption is ignored
+                let words: Vec<&str> = line.split(' ').collect();
+                match words[..] {
+                    ["done", row, len, adler] => {
+                        if let (Ok(row), Ok(len), Ok(adler)) =
+                            (row.parse::<usize>(), len.parse(), adler.parse())
+                        {
+                            finished = Some((row + 1, PngPosition { len, adler }));
+                        }
+                    }
+                    ["escape-range", low, high] => {
+                        escape_range = low.parse().ok().zip(high.parse().ok());
+                    }
+                    _ => {}
+                }
+            }
+            let file = OpenOptions::new().append(true).open(path)?;
+            Ok(Self {
+                file,
+                finished,
+                escape_range,
+            })
+        } else {
+            let mut file = File::create(path)?;
+            for line in &header {
+                writeln!(file, "{line}")?;
+            }
+            file.flush()?;
+            Ok(Self {
+                file,
+                finished,
+                escape_range,
+            })
+        }
+    }
+
+    /// The number of rows of tiles finished, and the position in the output
+    /// after them, if any are.
+    pub fn finished(&self) -> Option<(usize, PngPosition)> {
+        self.finished
+    }
+
+    /// Record that the tiles of `row` are finished, once they are written to
+    /// the output up to `position`.
+    pub fn mark_finished(&mut self, row: usize, position: PngPosition) -> io::Result<()> {
+        writeln!(self.file, "done {row} {} {}", position.len, position.adler)?;
+        self.file.flush()?;
+        self.finished = Some((row + 1, position));
+        Ok(())
+    }
+
+    /// The escape range recorded by [Checkpoint::record_escape_range].
+    pub fn escape_range(&self) -> Option<(usize, usize)> {
+        self.escape_range
+    }
+
+    pub fn record_escape_range(&mut self, (low, high): (usize, usize)) -> io::Result<()> {
+        writeln!(self.file, "escape-range {low} {high}")?;
+        self.file.flush()?;
+        self.escape_range = Some((low, high));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::Checkpoint;
+    use super::TileGrid;
+    use crate::export::PngPosition;
+
+    #[test]
+    fn grid_covers_image() {
+        let grid = TileGrid::new(250, 130, 100);
+        assert_eq!((grid.columns(), grid.rows(), grid.len()), (3, 2, 6));
+
+        let mut covered = vec![0; 250 * 130];
+        for tile in grid.tiles() {
+            for y in tile.y0..tile.y0 + tile.height {
+                for x in tile.x0..tile.x0 + tile.width {
+                    covered[x + y * 250] += 1;
+                }
+            }
+        }
+        assert!(covered.iter().all(|c| *c == 1));
+
+        let last = grid.tile(2, 1);
+        assert_eq!((last.width, last.height), (50, 30));
+    }
+
+    #[test]
+    fn checkpoint_resumes() {
+        let path =
+            std::env::temp_dir().join(format!("fraktal-checkpoint-test-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let grid = TileGrid::new(250, 130, 100);
+        let position = |len| PngPosition { len, adler: 7 };
+
+        let mut checkpoint = Checkpoint::open(&path, "state", &grid).unwrap();
+        assert_eq!(checkpoint.finished(), None);
+        checkpoint.record_escape_range((3, 90)).unwrap();
+        checkpoint.mark_finished(0, position(100)).unwrap();
+        checkpoint.mark_finished(1, position(200)).unwrap();
+        drop(checkpoint);
+        // Interrupted while recording the next row
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        write!(file, "done 2 3").unwrap();
+        drop(file);
+
+        let resumed = Checkpoint::open(&path, "state", &grid).unwrap();
+        assert_eq!(resumed.finished(), Some((2, position(200))));
+        assert_eq!(resumed.escape_range(), Some((3, 90)));
+        drop(resumed);
+
+        assert!(Checkpoint::open(&path, "other state", &grid).is_err());
+        assert!(Checkpoint::open(&path, "state", &TileGrid::new(250, 130, 50)).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
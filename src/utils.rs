@@ -1,6 +1,4 @@
 use std::fmt::Debug;
-use std::ops::Deref;
-use std::rc::Rc;
 
 use serde::Deserialize;
 use serde::Deserializer;
@@ -18,10 +16,21 @@ pub fn set_panic_hook() {
     console_error_panic_hook::set_once();
 }
 
+/// Whether `value` is the default, so that settings added after a
+/// serialization format was introduced can be left out of the serialized
+/// form of settings that don't use them.
+pub fn is_default<T>(value: &T) -> bool
+where
+    T: Default,
+    T: PartialEq,
+{
+    *value == T::default()
+}
+
 #[allow(unused_macros)]
 macro_rules! log_println {
     ( $( $t:tt )* ) => {
-        web_sys::console::log_1(&format!( $( $t )* ).into())
+        $crate::log::write($crate::log::Level::Info, &format!( $( $t )* ))
     }
 }
 #[allow(unused_imports)]
@@ -30,145 +39,11 @@ pub(crate) use log_println;
 #[allow(unused_macros)]
 macro_rules! error_println {
     ( $( $t:tt )* ) => {
-        web_sys::console::error_1(&format!( $( $t )* ).into())
+        $crate::log::write($crate::log::Level::Error, &format!( $( $t )* ))
     }
 }
-pub(crate) use error_println;
-
-#[allow(unused_macros)]
-macro_rules! log {
-    ( $t:expr ) => {
-        web_sys::console::log_1(&$t.into())
-    };
-    ( $t:expr, ) => {
-        web_sys::console::log_1(&$t.into())
-    };
-    ( $t1:expr, $t2:expr ) => {
-        web_sys::console::log_2(&$t1.into(), &$t2.into())
-    };
-    ( $t1:expr, $t2:expr, ) => {
-        web_sys::console::log_2(&$t1.into(), &$t2.into())
-    };
-    ( $t1:expr, $t2:expr, $t3:expr ) => {
-        web_sys::console::log_3(&$t1.into(), &$t2.into(), &$t3.into())
-    };
-    ( $t1:expr, $t2:expr, $t3:expr, ) => {
-        web_sys::console::log_3(&$t1.into(), &$t2.into(), &$t3.into())
-    };
-    ( $t1:expr, $t2:expr, $t3:expr, $t4:expr ) => {
-        web_sys::console::log_4(&$t1.into(), &$t2.into(), &$t3.into(), &$t4.into())
-    };
-    ( $t1:expr, $t2:expr, $t3:expr, $t4:expr, ) => {
-        web_sys::console::log_4(&$t1.into(), &$t2.into(), &$t3.into(), &$t4.into())
-    };
-    ( $t1:expr, $t2:expr, $t3:expr, $t4:expr, $t5:expr ) => {
-        web_sys::console::log_5(
-            &$t1.into(),
-            &$t2.into(),
-            &$t3.into(),
-            &$t4.into(),
-            &$t5.into(),
-        )
-    };
-    ( $t1:expr, $t2:expr, $t3:expr, $t4:expr, $t5:expr, ) => {
-        web_sys::console::log_5(
-            &$t1.into(),
-            &$t2.into(),
-            &$t3.into(),
-            &$t4.into(),
-            &$t5.into(),
-        )
-    };
-    ( $t1:expr, $t2:expr, $t3:expr, $t4:expr, $t5:expr, $t6:expr ) => {
-        web_sys::console::log_6(
-            &$t1.into(),
-            &$t2.into(),
-            &$t3.into(),
-            &$t4.into(),
-            &$t5.into(),
-            &$t6.into(),
-        )
-    };
-    ( $t1:expr, $t2:expr, $t3:expr, $t4:expr, $t5:expr, $t6:expr, ) => {
-        web_sys::console::log_6(
-            &$t1.into(),
-            &$t2.into(),
-            &$t3.into(),
-            &$t4.into(),
-            &$t5.into(),
-            &$t6.into(),
-        )
-    };
-}
 #[allow(unused_imports)]
-pub(crate) use log;
-
-#[allow(unused_macros)]
-macro_rules! error_log {
-    ( $t:expr ) => {
-        web_sys::console::error_1(&$t.into())
-    };
-    ( $t:expr, ) => {
-        web_sys::console::error_1(&$t.into())
-    };
-    ( $t1:expr, $t2:expr ) => {
-        web_sys::console::error_2(&$t1.into(), &$t2.into())
-    };
-    ( $t1:expr, $t2:expr, ) => {
-        web_sys::console::error_2(&$t1.into(), &$t2.into())
-    };
-    ( $t1:expr, $t2:expr, $t3:expr ) => {
-        web_sys::console::error_3(&$t1.into(), &$t2.into(), &$t3.into())
-    };
-    ( $t1:expr, $t2:expr, $t3:expr, ) => {
-        web_sys::console::error_3(&$t1.into(), &$t2.into(), &$t3.into())
-    };
-    ( $t1:expr, $t2:expr, $t3:expr, $t4:expr ) => {
-        web_sys::console::error_4(&$t1.into(), &$t2.into(), &$t3.into(), &$t4.into())
-    };
-    ( $t1:expr, $t2:expr, $t3:expr, $t4:expr, ) => {
-        web_sys::console::error_4(&$t1.into(), &$t2.into(), &$t3.into(), &$t4.into())
-    };
-    ( $t1:expr, $t2:expr, $t3:expr, $t4:expr, $t5:expr ) => {
-        web_sys::console::error_5(
-            &$t1.into(),
-            &$t2.into(),
-            &$t3.into(),
-            &$t4.into(),
-            &$t5.into(),
-        )
-    };
-    ( $t1:expr, $t2:expr, $t3:expr, $t4:expr, $t5:expr, ) => {
-        web_sys::console::error_5(
-            &$t1.into(),
-            &$t2.into(),
-            &$t3.into(),
-            &$t4.into(),
-            &$t5.into(),
-        )
-    };
-    ( $t1:expr, $t2:expr, $t3:expr, $t4:expr, $t5:expr, $t6:expr ) => {
-        web_sys::console::error_6(
-            &$t1.into(),
-            &$t2.into(),
-            &$t3.into(),
-            &$t4.into(),
-            &$t5.into(),
-            &$t6.into(),
-        )
-    };
-    ( $t1:expr, $t2:expr, $t3:expr, $t4:expr, $t5:expr, $t6:expr, ) => {
-        web_sys::console::error_6(
-            &$t1.into(),
-            &$t2.into(),
-            &$t3.into(),
-            &$t4.into(),
-            &$t5.into(),
-            &$t6.into(),
-        )
-    };
-}
-pub(crate) use error_log;
+pub(crate) use error_println;
 
 /// A container that keeps track of when its contained value has been mutated.
 #[derive(Clone, Debug)]
@@ -290,6 +165,11 @@ impl<T> Latch<T> {
         &self.current
     }
 
+    /// Access the queued value, or the current value if none is queued.
+    pub fn latest(&self) -> &T {
+        self.next.as_ref().unwrap_or(&self.current)
+    }
+
     /// If a new value is queued, update the current value to the new value and
     /// return `(old, &new)`.
     pub fn latch(&mut self) -> Option<(T, &T)> {
@@ -315,7 +195,7 @@ impl<T> Latch<T> {
     where
         F: Fn(&T) -> T,
     {
-        self.set(f(self.next.as_ref().unwrap_or(&self.current)))
+        self.set(f(self.latest()))
     }
 }
 
@@ -358,25 +238,3 @@ where
         self.current.serialize(serializer)
     }
 }
-
-#[derive(Debug)]
-pub struct PtrEq<T>(Rc<T>);
-
-impl<T> PartialEq for PtrEq<T> {
-    fn eq(&self, rhs: &Self) -> bool {
-        Rc::ptr_eq(&self.0, &rhs.0)
-    }
-}
-
-impl<T> Deref for PtrEq<T> {
-    type Target = Rc<T>;
-    fn deref(&self) -> &<Self as Deref>::Target {
-        &self.0
-    }
-}
-
-impl<T> PtrEq<T> {
-    pub fn new(v: Rc<T>) -> Self {
-        Self(v)
-    }
-}
@@ -0,0 +1,146 @@
+//! Logging to the browser console.
+
+use crate::log::Level;
+
+/// A [crate::log::LogSink] that writes to the browser console.
+pub fn log_to_console(level: Level, message: &str) {
+    match level {
+        Level::Info => web_sys::console::log_1(&message.into()),
+        Level::Error => web_sys::console::error_1(&message.into()),
+    }
+}
+
+#[allow(unused_macros)]
+macro_rules! log {
+    ( $t:expr ) => {
+        web_sys::console::log_1(&$t.into())
+    };
+    ( $t:expr, ) => {
+        web_sys::console::log_1(&$t.into())
+    };
+    ( $t1:expr, $t2:expr ) => {
+        web_sys::console::log_2(&$t1.into(), &$t2.into())
+    };
+    ( $t1:expr, $t2:expr, ) => {
+        web_sys::console::log_2(&$t1.into(), &$t2.into())
+    };
+    ( $t1:expr, $t2:expr, $t3:expr ) => {
+        web_sys::console::log_3(&$t1.into(), &$t2.into(), &$t3.into())
+    };
+    ( $t1:expr, $t2:expr, $t3:expr, ) => {
+        web_sys::console::log_3(&$t1.into(), &$t2.into(), &$t3.into())
+    };
+    ( $t1:expr, $t2:expr, $t3:expr, $t4:expr ) => {
+        web_sys::console::log_4(&$t1.into(), &$t2.into(), &$t3.into(), &$t4.into())
+    };
+    ( $t1:expr, $t2:expr, $t3:expr, $t4:expr, ) => {
+        web_sys::console::log_4(&$t1.into(), &$t2.into(), &$t3.into(), &$t4.into())
+    };
+    ( $t1:expr, $t2:expr, $t3:expr, $t4:expr, $t5:expr ) => {
+        web_sys::console::log_5(
+            &$t1.into(),
+            &$t2.into(),
+            &$t3.into(),
+            &$t4.into(),
+            &$t5.into(),
+        )
+    };
+    ( $t1:expr, $t2:expr, $t3:expr, $t4:expr, $t5:expr, ) => {
+        web_sys::console::log_5(
+            &$t1.into(),
+            &$t2.into(),
+            &$t3.into(),
+            &$t4.into(),
+            &$t5.into(),
+        )
+    };
+    ( $t1:expr, $t2:expr, $t3:expr, $t4:expr, $t5:expr, $t6:expr ) => {
+        web_sys::console::log_6(
+            &$t1.into(),
+            &$t2.into(),
+            &$t3.into(),
+            &$t4.into(),
+            &$t5.into(),
+            &$t6.into(),
+        )
+    };
+    ( $t1:expr, $t2:expr, $t3:expr, $t4:expr, $t5:expr, $t6:expr, ) => {
+        web_sys::console::log_6(
+            &$t1.into(),
+            &$t2.into(),
+            &$t3.into(),
+            &$t4.into(),
+            &$t5.into(),
+            &$t6.into(),
+        )
+    };
+}
+#[allow(unused_imports)]
+pub(crate) use log;
+
+#[allow(unused_macros)]
+macro_rules! error_log {
+    ( $t:expr ) => {
+        web_sys::console::error_1(&$t.into())
+    };
+    ( $t:expr, ) => {
+        web_sys::console::error_1(&$t.into())
+    };
+    ( $t1:expr, $t2:expr ) => {
+        web_sys::console::error_2(&$t1.into(), &$t2.into())
+    };
+    ( $t1:expr, $t2:expr, ) => {
+        web_sys::console::error_2(&$t1.into(), &$t2.into())
+    };
+    ( $t1:expr, $t2:expr, $t3:expr ) => {
+        web_sys::console::error_3(&$t1.into(), &$t2.into(), &$t3.into())
+    };
+    ( $t1:expr, $t2:expr, $t3:expr, ) => {
+        web_sys::console::error_3(&$t1.into(), &$t2.into(), &$t3.into())
+    };
+    ( $t1:expr, $t2:expr, $t3:expr, $t4:expr ) => {
+        web_sys::console::error_4(&$t1.into(), &$t2.into(), &$t3.into(), &$t4.into())
+    };
+    ( $t1:expr, $t2:expr, $t3:expr, $t4:expr, ) => {
+        web_sys::console::error_4(&$t1.into(), &$t2.into(), &$t3.into(), &$t4.into())
+    };
+    ( $t1:expr, $t2:expr, $t3:expr, $t4:expr, $t5:expr ) => {
+        web_sys::console::error_5(
+            &$t1.into(),
+            &$t2.into(),
+            &$t3.into(),
+            &$t4.into(),
+            &$t5.into(),
+        )
+    };
+    ( $t1:expr, $t2:expr, $t3:expr, $t4:expr, $t5:expr, ) => {
+        web_sys::console::error_5(
+            &$t1.into(),
+            &$t2.into(),
+            &$t3.into(),
+            &$t4.into(),
+            &$t5.into(),
+        )
+    };
+    ( $t1:expr, $t2:expr, $t3:expr, $t4:expr, $t5:expr, $t6:expr ) => {
+        web_sys::console::error_6(
+            &$t1.into(),
+            &$t2.into(),
+            &$t3.into(),
+            &$t4.into(),
+            &$t5.into(),
+            &$t6.into(),
+        )
+    };
+    ( $t1:expr, $t2:expr, $t3:expr, $t4:expr, $t5:expr, $t6:expr, ) => {
+        web_sys::console::error_6(
+            &$t1.into(),
+            &$t2.into(),
+            &$t3.into(),
+            &$t4.into(),
+            &$t5.into(),
+            &$t6.into(),
+        )
+    };
+}
+pub(crate) use error_log;
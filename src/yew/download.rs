@@ -0,0 +1,30 @@
+//! Saving files from the browser.
+
+use wasm_bindgen::JsCast;
+use wasm_bindgen::JsValue;
+use web_sys::window;
+use web_sys::Blob;
+use web_sys::BlobPropertyBag;
+use web_sys::HtmlAnchorElement;
+use web_sys::Url;
+
+/// Offer `bytes` for download as a file named `filename`.
+pub fn download(bytes: &[u8], mime_type: &str, filename: &str) -> Result<(), JsValue> {
+    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(bytes));
+    let blob = Blob::new_with_u8_array_sequence_and_options(
+        &parts,
+        BlobPropertyBag::new().type_(mime_type),
+    )?;
+    let url = Url::create_object_url_with_blob(&blob)?;
+
+    let anchor: HtmlAnchorElement = window()
+        .and_then(|window| window.document())
+        .ok_or_else(|| JsValue::from("No document"))?
+        .create_element("a")?
+        .dyn_into()?;
+    anchor.set_href(&url);
+    anchor.set_download(filename);
+    anchor.click();
+
+    Url::revoke_object_url(&url)
+}
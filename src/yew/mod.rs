@@ -1 +1,4 @@
+pub mod console;
+pub mod download;
+pub mod ptr_eq;
 pub mod state;
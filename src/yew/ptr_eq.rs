@@ -0,0 +1,24 @@
+use std::ops::Deref;
+use std::rc::Rc;
+
+#[derive(Debug)]
+pub struct PtrEq<T>(Rc<T>);
+
+impl<T> PartialEq for PtrEq<T> {
+    fn eq(&self, rhs: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &rhs.0)
+    }
+}
+
+impl<T> Deref for PtrEq<T> {
+    type Target = Rc<T>;
+    fn deref(&self) -> &<Self as Deref>::Target {
+        &self.0
+    }
+}
+
+impl<T> PtrEq<T> {
+    pub fn new(v: Rc<T>) -> Self {
+        Self(v)
+    }
+}